@@ -0,0 +1,83 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::verdict::Verdict;
+
+/// Outcome of a report or test step compared with the baseline of its
+/// project.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BaselineStatus {
+    /// Fails now but did not fail in the baseline or is not part of it
+    #[sea_orm(string_value = "NEW_FAILURE")]
+    NewFailure,
+    /// Failed in the baseline as well
+    #[sea_orm(string_value = "KNOWN_FAILURE")]
+    KnownFailure,
+    /// Succeeds now but failed in the baseline
+    #[sea_orm(string_value = "FIXED")]
+    Fixed,
+    /// Succeeds now and did not fail in the baseline
+    #[sea_orm(string_value = "PASSED")]
+    Passed,
+}
+
+impl BaselineStatus {
+    /// Compares a verdict with the one in the baseline, `None` if the
+    /// baseline lacks the result. Verdicts that neither succeed nor fail have
+    /// no status.
+    pub fn between(baseline: Option<Verdict>, verdict: Verdict) -> Option<Self> {
+        let failed_before = baseline.is_some_and(Verdict::is_failure);
+        if verdict.is_failure() {
+            Some(if failed_before {
+                BaselineStatus::KnownFailure
+            } else {
+                BaselineStatus::NewFailure
+            })
+        } else if verdict == Verdict::Success {
+            Some(if failed_before {
+                BaselineStatus::Fixed
+            } else {
+                BaselineStatus::Passed
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_between() {
+        assert_eq!(
+            BaselineStatus::between(Some(Verdict::Success), Verdict::Failed),
+            Some(BaselineStatus::NewFailure)
+        );
+        assert_eq!(
+            BaselineStatus::between(None, Verdict::Error),
+            Some(BaselineStatus::NewFailure)
+        );
+        assert_eq!(
+            BaselineStatus::between(Some(Verdict::Error), Verdict::Failed),
+            Some(BaselineStatus::KnownFailure)
+        );
+        assert_eq!(
+            BaselineStatus::between(Some(Verdict::Failed), Verdict::Success),
+            Some(BaselineStatus::Fixed)
+        );
+        assert_eq!(
+            BaselineStatus::between(Some(Verdict::Skipped), Verdict::Success),
+            Some(BaselineStatus::Passed)
+        );
+        assert_eq!(
+            BaselineStatus::between(Some(Verdict::Failed), Verdict::Skipped),
+            None
+        );
+    }
+}
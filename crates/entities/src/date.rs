@@ -0,0 +1,47 @@
+//! Helpers to keep the original UTC offset of dates.
+//!
+//! The databases only store the instant of a `DateTimeWithTimeZone`, so its
+//! offset is kept in a separate `date_offset` column.
+
+use chrono::FixedOffset;
+use sea_orm::prelude::DateTimeWithTimeZone;
+
+/// Offset of `date` from UTC in seconds
+pub fn offset_seconds(date: &DateTimeWithTimeZone) -> i32 {
+    date.offset().local_minus_utc()
+}
+
+/// `date` in UTC, as stored in the database.
+///
+/// SQLite compares dates as text, which only works if all have the same offset.
+pub fn to_utc(date: DateTimeWithTimeZone) -> DateTimeWithTimeZone {
+    with_offset(date, 0)
+}
+
+/// `date` converted to the given offset from UTC in seconds, invalid offsets
+/// leave the date unchanged
+pub fn with_offset(date: DateTimeWithTimeZone, offset_seconds: i32) -> DateTimeWithTimeZone {
+    match FixedOffset::east_opt(offset_seconds) {
+        Some(offset) => date.with_timezone(&offset),
+        None => date,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let date = DateTimeWithTimeZone::parse_from_rfc3339("2014-11-28T21:00:09+09:00").unwrap();
+        let offset = offset_seconds(&date);
+        assert_eq!(offset, 9 * 3600);
+        let utc = to_utc(date);
+        assert_eq!(utc.to_rfc3339(), "2014-11-28T12:00:09+00:00");
+        assert_eq!(
+            with_offset(utc, offset).to_rfc3339(),
+            "2014-11-28T21:00:09+09:00"
+        );
+        assert_eq!(with_offset(date, 86_400), date);
+    }
+}
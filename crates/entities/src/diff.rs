@@ -0,0 +1,334 @@
+//! Comparison of the test steps of two reports, e.g. to find regressions.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::report;
+use crate::test_step::{self, TreeNode};
+use crate::verdict::Verdict;
+use serde::{Deserialize, Serialize};
+
+/// How a test step changed from the old to the new report
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StepChange {
+    /// Only in the new report
+    Added,
+    /// Only in the old report
+    Removed,
+    /// Failed or ran into an error in the new report but not in the old one
+    NewlyFailing,
+    /// Failed or ran into an error in the old report and succeeded in the
+    /// new one
+    Fixed,
+    /// Any other change of the verdict
+    Changed,
+    Unchanged,
+}
+
+impl StepChange {
+    fn between(old: Verdict, new: Verdict) -> Self {
+        if old == new {
+            StepChange::Unchanged
+        } else if new.is_failure() && !old.is_failure() {
+            StepChange::NewlyFailing
+        } else if old.is_failure() && new == Verdict::Success {
+            StepChange::Fixed
+        } else {
+            StepChange::Changed
+        }
+    }
+}
+
+/// A test step of either or both reports
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepDiff {
+    /// Names of the parent steps from the top-level step down
+    pub path: Vec<String>,
+    pub step_number: i32,
+    pub name: String,
+    pub change: StepChange,
+    pub old: Option<test_step::Model>,
+    pub new: Option<test_step::Model>,
+    /// How much longer the step took in the new report, negative if faster
+    pub duration_change_ms: Option<i64>,
+}
+
+/// Differences between the test steps of two reports
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportDiff {
+    pub old: report::Model,
+    pub new: report::Model,
+    /// How much longer the new report took, negative if faster
+    pub duration_change_ms: Option<i64>,
+    /// Number of steps per kind of change
+    pub counts: BTreeMap<StepChange, usize>,
+    /// All steps of both reports in the order of the reports
+    pub steps: Vec<StepDiff>,
+}
+
+/// Position of a step in its report: the step numbers and names from the
+/// top-level step down to the step itself
+type StepKey = Vec<(i32, String)>;
+
+impl ReportDiff {
+    /// Compares both reports and their steps, see [`diff_steps`].
+    pub fn compute(
+        old: report::Model,
+        old_steps: Vec<test_step::Model>,
+        new: report::Model,
+        new_steps: Vec<test_step::Model>,
+    ) -> Self {
+        let steps = diff_steps(old_steps, new_steps);
+        let mut counts = BTreeMap::new();
+        for step in &steps {
+            *counts.entry(step.change).or_default() += 1;
+        }
+        ReportDiff {
+            duration_change_ms: duration_change(old.duration_ms, new.duration_ms),
+            old,
+            new,
+            counts,
+            steps,
+        }
+    }
+}
+
+impl StepDiff {
+    fn new(key: StepKey, old: Option<test_step::Model>, new: Option<test_step::Model>) -> Self {
+        let change = match (&old, &new) {
+            (Some(old), Some(new)) => StepChange::between(old.verdict, new.verdict),
+            (None, _) => StepChange::Added,
+            (_, None) => StepChange::Removed,
+        };
+        let duration_change_ms = duration_change(
+            old.as_ref().and_then(|step| step.duration_ms),
+            new.as_ref().and_then(|step| step.duration_ms),
+        );
+        let mut path: Vec<String> = key.into_iter().map(|(_, name)| name).collect();
+        let name = path.pop().unwrap_or_default();
+        let step_number = new
+            .as_ref()
+            .or(old.as_ref())
+            .map_or(0, |step| step.step_number);
+        StepDiff {
+            path,
+            step_number,
+            name,
+            change,
+            old,
+            new,
+            duration_change_ms,
+        }
+    }
+}
+
+/// Matches the steps of two reports by their step number and name as well as
+/// those of their parents.
+///
+/// Steps keep the order of the new report, removed steps are placed where
+/// they were in the old one.
+pub fn diff_steps(
+    old_steps: Vec<test_step::Model>,
+    new_steps: Vec<test_step::Model>,
+) -> Vec<StepDiff> {
+    let old_steps = flatten(TreeNode::build(old_steps));
+    let new_steps = flatten(TreeNode::build(new_steps));
+
+    // Several steps may share the same position, they are matched in order.
+    let mut positions: HashMap<&StepKey, VecDeque<usize>> = HashMap::new();
+    for (index, (key, _)) in old_steps.iter().enumerate() {
+        positions.entry(key).or_default().push_back(index);
+    }
+    let matches: Vec<Option<usize>> = new_steps
+        .iter()
+        .map(|(key, _)| positions.get_mut(key)?.pop_front())
+        .collect();
+
+    let mut is_matched = vec![false; old_steps.len()];
+    for index in matches.iter().flatten() {
+        is_matched[*index] = true;
+    }
+    let mut old_steps: Vec<_> = old_steps.into_iter().map(Some).collect();
+    let mut steps = Vec::new();
+    let mut next_old = 0;
+    for ((key, new_step), matched) in new_steps.into_iter().zip(matches) {
+        // Removed steps before the match keep their place, steps with the
+        // same number may have swapped places though.
+        if let Some(index) = matched.filter(|index| *index >= next_old) {
+            for removed in next_old..index {
+                if is_matched[removed] {
+                    continue;
+                }
+                if let Some((key, old_step)) = old_steps[removed].take() {
+                    steps.push(StepDiff::new(key, Some(old_step), None));
+                }
+            }
+            next_old = index + 1;
+        }
+        let old_step = matched.and_then(|index| old_steps[index].take().map(|(_, step)| step));
+        steps.push(StepDiff::new(key, old_step, Some(new_step)));
+    }
+    for (key, old_step) in old_steps.into_iter().flatten() {
+        steps.push(StepDiff::new(key, Some(old_step), None));
+    }
+    steps
+}
+
+/// `None` if a duration is missing or the difference does not fit into an `i64`.
+fn duration_change(old: Option<i64>, new: Option<i64>) -> Option<i64> {
+    new?.checked_sub(old?)
+}
+
+/// Lists the steps of the trees in pre-order together with their position.
+fn flatten(nodes: Vec<TreeNode>) -> Vec<(StepKey, test_step::Model)> {
+    fn visit(nodes: Vec<TreeNode>, parent: &StepKey, steps: &mut Vec<(StepKey, test_step::Model)>) {
+        for node in nodes {
+            let mut key = parent.clone();
+            key.push((node.step.step_number, node.step.name.clone()));
+            steps.push((key.clone(), node.step));
+            visit(node.children, &key, steps);
+        }
+    }
+    let mut steps = Vec::new();
+    visit(nodes, &Vec::new(), &mut steps);
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::prelude::DateTimeWithTimeZone;
+
+    fn report(id: i32, duration_ms: Option<i64>) -> report::Model {
+        report::Model {
+            id,
+            date: DateTimeWithTimeZone::parse_from_rfc3339("2014-11-28T21:00:09+09:00").unwrap(),
+            date_offset: 0,
+            duration_ms,
+            output: None,
+            project: "A".to_string(),
+            project_id: Some(1),
+            name: "Case.pkg".to_string(),
+            verdict: Verdict::Success,
+            declared_verdict: Verdict::Success,
+            computed_verdict: None,
+            derive_verdict: None,
+            test_run_id: None,
+            baseline_status: None,
+            properties: BTreeMap::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    fn step(
+        id: i32,
+        step_number: i32,
+        name: &str,
+        parent_id: Option<i32>,
+        verdict: Verdict,
+        duration_ms: Option<i64>,
+    ) -> test_step::Model {
+        test_step::Model {
+            id,
+            name: name.to_string(),
+            step_number,
+            date: DateTimeWithTimeZone::parse_from_rfc3339("2014-11-28T21:00:09+09:00").unwrap(),
+            date_offset: 0,
+            verdict,
+            report_id: 1,
+            parent_id,
+            duration_ms,
+            output: None,
+            message: None,
+            expected: None,
+            actual: None,
+            unit: None,
+            failure_category: None,
+            baseline_status: None,
+        }
+    }
+
+    #[test]
+    fn test_compute() {
+        let diff = ReportDiff::compute(
+            report(1, Some(1000)),
+            vec![
+                step(1, 1, "Init", None, Verdict::Success, Some(100)),
+                step(2, 2, "Read", None, Verdict::Success, None),
+                step(3, 1, "Bus", Some(2), Verdict::Failed, None),
+                step(4, 3, "Gone", None, Verdict::Success, None),
+                step(5, 4, "Write", None, Verdict::Success, None),
+            ],
+            report(2, Some(800)),
+            vec![
+                step(11, 1, "Init", None, Verdict::Success, Some(150)),
+                step(12, 2, "Read", None, Verdict::Failed, None),
+                step(13, 1, "Bus", Some(12), Verdict::Success, None),
+                step(14, 2, "New", Some(12), Verdict::Success, None),
+                step(15, 4, "Write", None, Verdict::Skipped, None),
+            ],
+        );
+
+        let changes: Vec<_> = diff
+            .steps
+            .iter()
+            .map(|step| (step.name.as_str(), step.change))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("Init", StepChange::Unchanged),
+                ("Read", StepChange::NewlyFailing),
+                ("Bus", StepChange::Fixed),
+                ("New", StepChange::Added),
+                ("Gone", StepChange::Removed),
+                ("Write", StepChange::Changed),
+            ]
+        );
+        assert_eq!(diff.steps[2].path, ["Read"]);
+        assert_eq!(diff.steps[0].duration_change_ms, Some(50));
+        assert_eq!(diff.steps[1].duration_change_ms, None);
+        assert_eq!(diff.duration_change_ms, Some(-200));
+        assert_eq!(diff.counts[&StepChange::Unchanged], 1);
+        assert_eq!(diff.counts[&StepChange::Removed], 1);
+    }
+
+    #[test]
+    fn test_compute_swapped_steps() {
+        let diff = ReportDiff::compute(
+            report(1, None),
+            vec![
+                step(1, 1, "B", None, Verdict::Success, None),
+                step(2, 1, "A", None, Verdict::Success, None),
+                step(3, 2, "Gone", None, Verdict::Success, None),
+            ],
+            report(2, None),
+            vec![
+                step(11, 1, "A", None, Verdict::Success, None),
+                step(12, 1, "B", None, Verdict::Failed, None),
+            ],
+        );
+        let changes: Vec<_> = diff
+            .steps
+            .iter()
+            .map(|step| (step.name.as_str(), step.change))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("A", StepChange::Unchanged),
+                ("B", StepChange::NewlyFailing),
+                ("Gone", StepChange::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duration_change() {
+        assert_eq!(duration_change(Some(250), Some(50)), Some(-200));
+        assert_eq!(duration_change(None, Some(50)), None);
+        assert_eq!(duration_change(Some(0), Some(i64::MAX)), Some(i64::MAX));
+        assert_eq!(duration_change(Some(-1), Some(i64::MAX)), None);
+        assert_eq!(duration_change(Some(i64::MAX), Some(i64::MIN)), None);
+    }
+}
@@ -0,0 +1,51 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Why a test step did not succeed.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FailureCategory {
+    /// The actual value did not match the expected one
+    #[sea_orm(string_value = "ASSERTION")]
+    Assertion,
+    /// The step did not finish in time
+    #[sea_orm(string_value = "TIMEOUT")]
+    Timeout,
+    /// The test itself crashed, e.g. an uncaught exception or panic
+    #[sea_orm(string_value = "EXCEPTION")]
+    Exception,
+    /// The test bench or environment was not available or misbehaved
+    #[sea_orm(string_value = "ENVIRONMENT")]
+    Environment,
+    #[sea_orm(string_value = "OTHER")]
+    Other,
+}
+
+impl FailureCategory {
+    /// Name of the category as stored in the database and used in JSON.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FailureCategory::Assertion => "ASSERTION",
+            FailureCategory::Timeout => "TIMEOUT",
+            FailureCategory::Exception => "EXCEPTION",
+            FailureCategory::Environment => "ENVIRONMENT",
+            FailureCategory::Other => "OTHER",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::Iterable;
+
+    #[test]
+    fn test_as_str() {
+        for category in FailureCategory::iter() {
+            assert_eq!(category.as_str(), category.to_value());
+        }
+    }
+}
@@ -2,5 +2,17 @@
 
 pub mod prelude;
 
+pub mod attachment;
+pub mod baseline;
+pub mod date;
+pub mod diff;
+pub mod failure_category;
+pub mod measurement;
+pub mod project;
 pub mod report;
+pub mod report_property;
+pub mod report_tag;
+pub mod test_run;
 pub mod test_step;
+pub mod triage;
+pub mod verdict;
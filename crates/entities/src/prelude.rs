@@ -1,4 +1,15 @@
 //! SeaORM Entity. Generated by sea-orm-codegen 0.9.1
 
+pub use super::attachment::Entity as Attachment;
+pub use super::baseline::BaselineStatus;
+pub use super::failure_category::FailureCategory;
+pub use super::measurement::Entity as Measurement;
+pub use super::project::Entity as Project;
 pub use super::report::Entity as Report;
+pub use super::report_property::Entity as ReportProperty;
+pub use super::report_tag::Entity as ReportTag;
+pub use super::test_run::Entity as TestRun;
 pub use super::test_step::Entity as TestStep;
+pub use super::triage::Entity as Triage;
+pub use super::triage::{TriageCategory, TriageStatus};
+pub use super::verdict::Verdict;
@@ -1,7 +1,10 @@
 //! SeaORM Entity. Generated by sea-orm-codegen 0.9.1
 
+use std::collections::BTreeMap;
 use std::fmt::Display;
 
+use crate::baseline::BaselineStatus;
+use crate::verdict::Verdict;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -12,15 +15,88 @@ pub struct Model {
     #[serde(default)]
     pub id: i32,
     pub date: DateTimeWithTimeZone,
+    /// Offset of `date` from UTC in seconds as originally posted, see
+    /// [`Model::with_original_offset`]
+    #[serde(skip)]
+    pub date_offset: i32,
+    /// Duration of the whole report in milliseconds, `date` is its start
+    #[serde(default)]
+    pub duration_ms: Option<i64>,
+    /// Captured output that does not belong to a single step, e.g. of the
+    /// setup of a test suite
+    #[serde(default)]
+    pub output: Option<String>,
+    /// Name of the project, kept in sync with `project_id`
     pub project: String,
+    #[serde(default)]
+    pub project_id: Option<i32>,
     pub name: String,
-    pub verdict: String,
+    /// Effective verdict of the report
+    pub verdict: Verdict,
+    /// Verdict as posted by the client
+    #[serde(default)]
+    pub declared_verdict: Verdict,
+    /// Worst verdict of all test steps, `None` if there are no steps
+    #[serde(default)]
+    pub computed_verdict: Option<Verdict>,
+    /// Whether `verdict` follows `computed_verdict`, defaults to the setting
+    /// of the project
+    #[serde(default)]
+    pub derive_verdict: Option<bool>,
+    /// Test run the report was produced by, if any
+    #[serde(default)]
+    pub test_run_id: Option<i32>,
+    /// Verdict compared with the baseline of the project when the report or
+    /// its steps were uploaded, `None` without a baseline
+    #[serde(default)]
+    pub baseline_status: Option<BaselineStatus>,
+    /// Context of the report like the software version or the test bench,
+    /// kept in the `report_property` table
+    #[sea_orm(ignore)]
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+    /// Labels of the report, kept in the `report_tag` table
+    #[sea_orm(ignore)]
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Project,
+    #[sea_orm(
+        belongs_to = "super::test_run::Entity",
+        from = "Column::TestRunId",
+        to = "super::test_run::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    TestRun,
     #[sea_orm(has_many = "super::test_step::Entity")]
     TestStep,
+    #[sea_orm(has_many = "super::report_property::Entity")]
+    ReportProperty,
+    #[sea_orm(has_many = "super::report_tag::Entity")]
+    ReportTag,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl Related<super::test_run::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TestRun.def()
+    }
 }
 
 impl Related<super::test_step::Entity> for Entity {
@@ -29,10 +105,37 @@ impl Related<super::test_step::Entity> for Entity {
     }
 }
 
+impl Related<super::report_property::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ReportProperty.def()
+    }
+}
+
+impl Related<super::report_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ReportTag.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
+impl Model {
+    /// Converts `date` back to its original offset, the database only
+    /// returns it in UTC.
+    pub fn with_original_offset(mut self) -> Self {
+        self.date = crate::date::with_offset(self.date, self.date_offset);
+        self
+    }
+}
+
 impl Display for Model {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {} ({})", self.name, self.verdict, self.date)
+        write!(
+            f,
+            "{}: {} ({})",
+            self.name,
+            self.verdict.as_str(),
+            self.date
+        )
     }
 }
@@ -1,7 +1,11 @@
 //! SeaORM Entity. Generated by sea-orm-codegen 0.9.1
 
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 
+use crate::baseline::BaselineStatus;
+use crate::failure_category::FailureCategory;
+use crate::verdict::Verdict;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -14,9 +18,38 @@ pub struct Model {
     pub name: String,
     pub step_number: i32,
     pub date: DateTimeWithTimeZone,
-    pub verdict: String,
+    /// Offset of `date` from UTC in seconds as originally posted, see
+    /// [`Model::with_original_offset`]
+    #[serde(skip)]
+    pub date_offset: i32,
+    pub verdict: Verdict,
     #[serde(default)]
     pub report_id: i32,
+    /// Step this step is a sub-step of, `None` for top-level steps
+    #[serde(default)]
+    pub parent_id: Option<i32>,
+    /// Duration of the step in milliseconds, `date` is its start
+    #[serde(default)]
+    pub duration_ms: Option<i64>,
+    /// Captured output of the step, e.g. stdout of a test
+    #[serde(default)]
+    pub output: Option<String>,
+    /// Explanation of the verdict, e.g. why the step failed
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Value the step expected, in `unit` if given
+    #[serde(default)]
+    pub expected: Option<String>,
+    /// Value the step actually got, in `unit` if given
+    #[serde(default)]
+    pub actual: Option<String>,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub failure_category: Option<FailureCategory>,
+    /// Verdict compared with the same step in the baseline of the project
+    #[serde(default)]
+    pub baseline_status: Option<BaselineStatus>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -29,6 +62,14 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     Report,
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::ParentId",
+        to = "Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Parent,
 }
 
 impl Related<super::report::Entity> for Entity {
@@ -39,8 +80,120 @@ impl Related<super::report::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
+impl Model {
+    /// Converts `date` back to its original offset, the database only
+    /// returns it in UTC.
+    pub fn with_original_offset(mut self) -> Self {
+        self.date = crate::date::with_offset(self.date, self.date_offset);
+        self
+    }
+}
+
 impl Display for Model {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {}: {}", self.step_number, self.name, self.verdict)
+        write!(
+            f,
+            "{} {}: {}",
+            self.step_number,
+            self.name,
+            self.verdict.as_str()
+        )
+    }
+}
+
+/// A test step with all its sub-steps
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeNode {
+    #[serde(flatten)]
+    pub step: Model,
+    /// Worst verdict of the step and all its sub-steps
+    pub computed_verdict: Verdict,
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    /// Arranges the steps of a report as trees ordered by step number.
+    ///
+    /// Steps whose parent is not part of `steps` become roots.
+    pub fn build(steps: Vec<Model>) -> Vec<TreeNode> {
+        let ids: HashSet<i32> = steps.iter().map(|step| step.id).collect();
+        let mut children: HashMap<Option<i32>, Vec<Model>> = HashMap::new();
+        for step in steps {
+            let parent_id = step.parent_id.filter(|parent_id| ids.contains(parent_id));
+            children.entry(parent_id).or_default().push(step);
+        }
+        Self::build_level(None, &mut children)
+    }
+
+    fn build_level(
+        parent_id: Option<i32>,
+        children: &mut HashMap<Option<i32>, Vec<Model>>,
+    ) -> Vec<TreeNode> {
+        let mut steps = children.remove(&parent_id).unwrap_or_default();
+        steps.sort_by_key(|step| (step.step_number, step.id));
+        steps
+            .into_iter()
+            .map(|step| {
+                let children = Self::build_level(Some(step.id), children);
+                let computed_verdict = Verdict::worst(
+                    std::iter::once(step.verdict)
+                        .chain(children.iter().map(|child| child.computed_verdict)),
+                );
+                TreeNode {
+                    step,
+                    computed_verdict,
+                    children,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(id: i32, step_number: i32, parent_id: Option<i32>, verdict: Verdict) -> Model {
+        Model {
+            id,
+            name: format!("Step {id}"),
+            step_number,
+            date: DateTimeWithTimeZone::parse_from_rfc3339("2014-11-28T21:00:09+09:00").unwrap(),
+            date_offset: 0,
+            verdict,
+            report_id: 1,
+            parent_id,
+            duration_ms: None,
+            output: None,
+            message: None,
+            expected: None,
+            actual: None,
+            unit: None,
+            failure_category: None,
+            baseline_status: None,
+        }
+    }
+
+    #[test]
+    fn test_build_tree() {
+        let tree = TreeNode::build(vec![
+            step(1, 2, None, Verdict::Success),
+            step(2, 1, None, Verdict::Success),
+            step(3, 2, Some(2), Verdict::Failed),
+            step(4, 1, Some(2), Verdict::Success),
+            step(5, 1, Some(3), Verdict::Error),
+            step(6, 3, Some(42), Verdict::None),
+        ]);
+
+        let ids = |nodes: &[TreeNode]| nodes.iter().map(|node| node.step.id).collect::<Vec<_>>();
+        assert_eq!(ids(&tree), [2, 1, 6]);
+        assert_eq!(ids(&tree[0].children), [4, 3]);
+        assert_eq!(ids(&tree[0].children[1].children), [5]);
+        assert!(tree[1].children.is_empty());
+
+        assert_eq!(tree[0].computed_verdict, Verdict::Error);
+        assert_eq!(tree[0].children[0].computed_verdict, Verdict::Success);
+        assert_eq!(tree[0].children[1].computed_verdict, Verdict::Error);
+        assert_eq!(tree[1].computed_verdict, Verdict::Success);
     }
 }
@@ -0,0 +1,132 @@
+use std::cmp::Ordering;
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Verdict of a report or test step.
+///
+/// Verdicts are ordered by severity, so the verdict of a collection of
+/// results is the maximum of the individual verdicts (see [`Verdict::worst`]).
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Verdict {
+    #[default]
+    #[sea_orm(string_value = "NONE")]
+    None,
+    #[sea_orm(string_value = "SUCCESS")]
+    Success,
+    #[sea_orm(string_value = "INCONCLUSIVE")]
+    Inconclusive,
+    #[sea_orm(string_value = "FAILED")]
+    Failed,
+    #[sea_orm(string_value = "ERROR")]
+    Error,
+    #[sea_orm(string_value = "SKIPPED")]
+    Skipped,
+}
+
+impl Verdict {
+    /// Verdicts of results that failed or ran into an error, e.g. for
+    /// filtering in queries.
+    pub const FAILURES: [Verdict; 2] = [Verdict::Failed, Verdict::Error];
+
+    /// Severity of the verdict, higher is worse.
+    ///
+    /// A skipped result does not make a successful one any worse, but is
+    /// still more meaningful than no verdict at all.
+    pub fn severity(self) -> u8 {
+        match self {
+            Verdict::None => 0,
+            Verdict::Skipped => 1,
+            Verdict::Success => 2,
+            Verdict::Inconclusive => 3,
+            Verdict::Failed => 4,
+            Verdict::Error => 5,
+        }
+    }
+
+    /// Returns the most severe of the given verdicts or [`Verdict::None`] if
+    /// there are none.
+    pub fn worst(verdicts: impl IntoIterator<Item = Verdict>) -> Verdict {
+        verdicts.into_iter().max().unwrap_or(Verdict::None)
+    }
+
+    /// Whether the result failed or ran into an error, see
+    /// [`Verdict::FAILURES`].
+    pub fn is_failure(self) -> bool {
+        Self::FAILURES.contains(&self)
+    }
+
+    /// Name of the verdict as stored in the database and used in JSON.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verdict::None => "NONE",
+            Verdict::Success => "SUCCESS",
+            Verdict::Inconclusive => "INCONCLUSIVE",
+            Verdict::Failed => "FAILED",
+            Verdict::Error => "ERROR",
+            Verdict::Skipped => "SKIPPED",
+        }
+    }
+}
+
+impl PartialOrd for Verdict {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Verdict {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.severity().cmp(&other.severity())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::Iterable;
+
+    #[test]
+    fn test_worst() {
+        assert_eq!(Verdict::worst([]), Verdict::None);
+        assert_eq!(
+            Verdict::worst([Verdict::Success, Verdict::Skipped]),
+            Verdict::Success
+        );
+        assert_eq!(
+            Verdict::worst([Verdict::Failed, Verdict::Error, Verdict::Success]),
+            Verdict::Error
+        );
+        assert_eq!(
+            Verdict::worst([Verdict::Inconclusive, Verdict::None]),
+            Verdict::Inconclusive
+        );
+    }
+
+    #[test]
+    fn test_as_str() {
+        for verdict in Verdict::iter() {
+            assert_eq!(verdict.as_str(), verdict.to_value());
+        }
+    }
+
+    #[test]
+    fn test_is_failure() {
+        let failures: Vec<_> = Verdict::iter().filter(|v| v.is_failure()).collect();
+        assert_eq!(failures, Verdict::FAILURES);
+    }
+}
@@ -9,4 +9,7 @@ pub struct Cli {
     /// Result guide host port
     #[arg(short, long)]
     pub port: Option<u16>,
+    /// Name recorded as author of triage entries
+    #[arg(long, env = "USER")]
+    pub author: Option<String>,
 }
@@ -3,10 +3,16 @@ mod cli;
 use clap::Parser;
 use cli::Cli;
 use eframe::egui;
+use entities::baseline::BaselineStatus;
+use entities::diff::{ReportDiff, StepChange};
 use entities::report::Model as Report;
 use entities::test_step::Model as TestStep;
+use entities::test_step::TreeNode;
+use entities::triage::{Model as Triage, TriageCategory, TriageStatus};
+use eyre::eyre;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::Sender;
 use std::sync::mpsc::TryRecvError;
@@ -23,30 +29,93 @@ fn main() {
 
 struct ResultGuideGui {
     reports: Option<Vec<Report>>,
-    test_steps: HashMap<i32, Vec<TestStep>>,
+    /// Project ids and names of the reports the server considers flaky
+    flaky: HashSet<(i32, String)>,
+    test_steps: HashMap<i32, Vec<TreeNode>>,
+    /// Reports selected for comparison, at most two
+    compare: Vec<i32>,
+    diff: Option<ReportDiff>,
+    /// Triage entries of the expanded reports
+    triage: HashMap<i32, Vec<Triage>>,
+    /// Triage entries being edited per report
+    triage_forms: HashMap<i32, TriageForm>,
+    author: String,
     last_error: Option<eyre::Report>,
     tx: Sender<ApiRequest>,
     rx: Receiver<ApiResponse>,
     waiting_for_response: u8,
 }
 
+/// Error document returned by the server for failed requests
+#[derive(Deserialize)]
+struct ApiError {
+    code: u16,
+    message: String,
+}
+
 impl ResultGuideGui {
-    fn retrieve_reports(url: &str) -> eyre::Result<Vec<Report>> {
-        let command_url = format!("{url}/reports");
-        let response = reqwest::blocking::get(command_url)?.error_for_status()?;
-        let body = response.text()?;
+    fn get<T: DeserializeOwned>(command_url: &str) -> eyre::Result<T> {
+        let body = Self::body(reqwest::blocking::get(command_url)?)?;
         Ok(serde_json::from_str(&body)?)
     }
 
-    fn retrieve_test_steps(url: &str, report_id: i32) -> eyre::Result<Vec<TestStep>> {
-        let command_url = format!("{url}/reports/{report_id}/test_steps");
-        let response = reqwest::blocking::get(command_url)?.error_for_status()?;
+    fn post<T: Serialize>(command_url: &str, content: &T) -> eyre::Result<String> {
+        let response = reqwest::blocking::Client::new()
+            .post(command_url)
+            .body(serde_json::to_string(content)?)
+            .send()?;
+        Self::body(response)
+    }
+
+    /// Body of a successful response, the error of the server otherwise.
+    fn body(response: reqwest::blocking::Response) -> eyre::Result<String> {
+        let status = response.status();
         let body = response.text()?;
-        Ok(serde_json::from_str(&body)?)
+        if !status.is_success() {
+            return Err(match serde_json::from_str::<ApiError>(&body) {
+                Ok(error) => eyre!("{} ({})", error.message, error.code),
+                Err(_) => eyre!("Request failed with {status}"),
+            });
+        }
+        Ok(body)
+    }
+
+    fn retrieve_reports(url: &str) -> eyre::Result<Vec<Report>> {
+        Self::get(&format!("{url}/reports"))
+    }
+
+    /// Retrieves the flaky test cases of all projects the reports belong to.
+    fn retrieve_flaky(url: &str, reports: &[Report]) -> eyre::Result<HashSet<(i32, String)>> {
+        let project_ids: BTreeSet<i32> = reports.iter().filter_map(|r| r.project_id).collect();
+        let mut flaky = HashSet::new();
+        for project_id in project_ids {
+            let cases: Vec<FlakyTestCase> =
+                Self::get(&format!("{url}/projects/{project_id}/flaky"))?;
+            flaky.extend(cases.into_iter().map(|case| (project_id, case.name)));
+        }
+        Ok(flaky)
+    }
+
+    fn retrieve_test_steps(url: &str, report_id: i32) -> eyre::Result<Vec<TreeNode>> {
+        Self::get(&format!("{url}/reports/{report_id}/test_steps/tree"))
+    }
+
+    fn retrieve_diff(url: &str, old_id: i32, new_id: i32) -> eyre::Result<ReportDiff> {
+        Self::get(&format!("{url}/reports/{old_id}/diff/{new_id}"))
+    }
+
+    fn retrieve_triage(url: &str, report_id: i32) -> eyre::Result<Vec<Triage>> {
+        Self::get(&format!("{url}/reports/{report_id}/triage"))
+    }
+
+    fn add_triage(url: &str, report_id: i32, form: &TriageForm) -> eyre::Result<Vec<Triage>> {
+        Self::post(&format!("{url}/reports/{report_id}/triage"), form)?;
+        Self::retrieve_triage(url, report_id)
     }
 
     fn new(cli: Cli) -> Self {
         let (req_tx, req_rx) = std::sync::mpsc::channel();
+        let author = cli.author.clone().unwrap_or_default();
         let (resp_tx, resp_rx) = std::sync::mpsc::channel();
 
         std::thread::spawn(move || {
@@ -56,17 +125,42 @@ impl ResultGuideGui {
             loop {
                 match req_rx.recv() {
                     Ok(ApiRequest::GetReports) => {
+                        let response = ResultGuideGui::retrieve_reports(&url).and_then(|reports| {
+                            let flaky = ResultGuideGui::retrieve_flaky(&url, &reports)?;
+                            Ok(ApiResponse::Reports(reports, flaky))
+                        });
                         resp_tx
-                            .send(match ResultGuideGui::retrieve_reports(&url) {
-                                Ok(reports) => ApiResponse::Reports(reports),
-                                Err(e) => ApiResponse::Error(e),
-                            })
+                            .send(response.unwrap_or_else(ApiResponse::Error))
                             .unwrap();
                     }
                     Ok(ApiRequest::GetTestSteps(report_id)) => {
                         resp_tx
                             .send(match ResultGuideGui::retrieve_test_steps(&url, report_id) {
-                                Ok(test_steps) => ApiResponse::TestSteps(test_steps),
+                                Ok(test_steps) => ApiResponse::TestSteps(report_id, test_steps),
+                                Err(e) => ApiResponse::Error(e),
+                            })
+                            .unwrap();
+                    }
+                    Ok(ApiRequest::CompareReports(old_id, new_id)) => {
+                        resp_tx
+                            .send(match ResultGuideGui::retrieve_diff(&url, old_id, new_id) {
+                                Ok(diff) => ApiResponse::Diff(Box::new(diff)),
+                                Err(e) => ApiResponse::Error(e),
+                            })
+                            .unwrap();
+                    }
+                    Ok(ApiRequest::GetTriage(report_id)) => {
+                        resp_tx
+                            .send(match ResultGuideGui::retrieve_triage(&url, report_id) {
+                                Ok(entries) => ApiResponse::Triage(report_id, entries),
+                                Err(e) => ApiResponse::Error(e),
+                            })
+                            .unwrap();
+                    }
+                    Ok(ApiRequest::AddTriage(report_id, form)) => {
+                        resp_tx
+                            .send(match ResultGuideGui::add_triage(&url, report_id, &form) {
+                                Ok(entries) => ApiResponse::Triage(report_id, entries),
                                 Err(e) => ApiResponse::Error(e),
                             })
                             .unwrap();
@@ -78,7 +172,13 @@ impl ResultGuideGui {
 
         Self {
             reports: None,
+            flaky: HashSet::new(),
             test_steps: HashMap::new(),
+            compare: Vec::new(),
+            diff: None,
+            triage: HashMap::new(),
+            triage_forms: HashMap::new(),
+            author,
             last_error: None,
             tx: req_tx,
             rx: resp_rx,
@@ -90,15 +190,39 @@ impl ResultGuideGui {
 enum ApiRequest {
     GetReports,
     GetTestSteps(i32),
+    /// Ids of the old and the new report
+    CompareReports(i32, i32),
+    GetTriage(i32),
+    AddTriage(i32, TriageForm),
 }
 enum ApiResponse {
     #[allow(unused)]
     Raw(String),
-    Reports(Vec<Report>),
-    TestSteps(Vec<TestStep>),
+    Reports(Vec<Report>, HashSet<(i32, String)>),
+    TestSteps(i32, Vec<TreeNode>),
+    Diff(Box<ReportDiff>),
+    Triage(i32, Vec<Triage>),
     Error(eyre::Report),
 }
 
+impl ResultGuideGui {
+    /// The selected reports as old and new report, ordered by their date.
+    fn compare_order(&self) -> Option<(i32, i32)> {
+        let date = |id: i32| {
+            self.reports
+                .iter()
+                .flatten()
+                .find(|report| report.id == id)
+                .map(|report| report.date)
+        };
+        match self.compare[..] {
+            [a, b] if date(a) <= date(b) => Some((a, b)),
+            [a, b] => Some((b, a)),
+            _ => None,
+        }
+    }
+}
+
 impl eframe::App for ResultGuideGui {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -113,18 +237,41 @@ impl eframe::App for ResultGuideGui {
                     self.tx.send(ApiRequest::GetTestSteps(first.id)).unwrap()
                 }
             }
+            if ui
+                .add_enabled(
+                    self.compare.len() == 2,
+                    egui::Button::new("Compare selected reports"),
+                )
+                .clicked()
+            {
+                if let Some((old_id, new_id)) = self.compare_order() {
+                    self.waiting_for_response = self.waiting_for_response.saturating_add(1);
+                    self.tx
+                        .send(ApiRequest::CompareReports(old_id, new_id))
+                        .unwrap()
+                }
+            }
             match self.rx.try_recv() {
-                Ok(ApiResponse::Reports(resp)) => {
+                Ok(ApiResponse::Reports(resp, flaky)) => {
                     self.waiting_for_response = self.waiting_for_response.saturating_sub(1);
                     self.reports = Some(resp);
+                    self.flaky = flaky;
                     self.last_error = None;
                 }
-                Ok(ApiResponse::TestSteps(resp)) => {
-                    if let Some(first_report) = resp.first() {
-                        self.waiting_for_response = self.waiting_for_response.saturating_sub(1);
-                        self.test_steps.insert(first_report.report_id, resp);
-                        self.last_error = None;
-                    }
+                Ok(ApiResponse::TestSteps(report_id, resp)) => {
+                    self.waiting_for_response = self.waiting_for_response.saturating_sub(1);
+                    self.test_steps.insert(report_id, resp);
+                    self.last_error = None;
+                }
+                Ok(ApiResponse::Diff(diff)) => {
+                    self.waiting_for_response = self.waiting_for_response.saturating_sub(1);
+                    self.diff = Some(*diff);
+                    self.last_error = None;
+                }
+                Ok(ApiResponse::Triage(report_id, entries)) => {
+                    self.waiting_for_response = self.waiting_for_response.saturating_sub(1);
+                    self.triage.insert(report_id, entries);
+                    self.last_error = None;
                 }
                 Ok(ApiResponse::Error(e)) => {
                     self.waiting_for_response = self.waiting_for_response.saturating_sub(1);
@@ -134,31 +281,55 @@ impl eframe::App for ResultGuideGui {
                 _ => (),
             }
             egui::Grid::new("reports")
-                .num_columns(1)
+                .num_columns(2)
                 .striped(true)
                 .show(ui, |ui| {
                     for report in self.reports.iter().flatten() {
-                        let report_details = ui.collapsing(report.to_string(), |ui| {
-                            egui::Grid::new("steps")
-                                .num_columns(4)
-                                .striped(true)
-                                .show(ui, |ui| {
-                                    if let Some(steps) = self.test_steps.get(&report.id) {
-                                        for step in steps {
-                                            ui.label(step.step_number.to_string());
-                                            ui.label(&step.name);
-                                            ui.label(&step.verdict);
-                                            ui.label(step.date.to_string());
-                                            ui.end_row();
-                                        }
-                                    }
-                                });
+                        let mut selected = self.compare.contains(&report.id);
+                        if ui.checkbox(&mut selected, "").changed() {
+                            self.compare.retain(|id| *id != report.id);
+                            if selected {
+                                self.compare.push(report.id);
+                                if self.compare.len() > 2 {
+                                    self.compare.remove(0);
+                                }
+                            }
+                        }
+                        let flaky = report
+                            .project_id
+                            .is_some_and(|id| self.flaky.contains(&(id, report.name.clone())));
+                        let header = report_header(ui, report, flaky);
+                        let report_details = ui.collapsing(header, |ui| {
+                            let steps = self.test_steps.get(&report.id);
+                            if let Some(steps) = steps {
+                                show_test_steps(ui, steps);
+                            }
+                            let entries = self.triage.get(&report.id);
+                            if report.verdict.is_failure() || entries.is_some_and(|e| !e.is_empty())
+                            {
+                                let form = self
+                                    .triage_forms
+                                    .entry(report.id)
+                                    .or_insert_with(|| TriageForm::new(self.author.clone()));
+                                let entries = entries.map(Vec::as_slice).unwrap_or_default();
+                                if show_triage(ui, report.id, entries, steps, form) {
+                                    self.waiting_for_response =
+                                        self.waiting_for_response.saturating_add(1);
+                                    self.tx
+                                        .send(ApiRequest::AddTriage(report.id, form.clone()))
+                                        .unwrap();
+                                    form.ticket.clear();
+                                    form.comment.clear();
+                                }
+                            }
                         });
                         if report_details.header_response.clicked() && report_details.openness < 0.5
                         {
                             // self.test_steps.remove(&report.id);  // jedes Mal löschen damit man mehrmals Aufklappen kann um die Verzögerung besser zu beurteilen
                             self.waiting_for_response = self.waiting_for_response.saturating_add(1);
-                            self.tx.send(ApiRequest::GetTestSteps(report.id)).unwrap()
+                            self.tx.send(ApiRequest::GetTestSteps(report.id)).unwrap();
+                            self.waiting_for_response = self.waiting_for_response.saturating_add(1);
+                            self.tx.send(ApiRequest::GetTriage(report.id)).unwrap()
                         }
                         ui.end_row();
                     }
@@ -168,8 +339,354 @@ impl eframe::App for ResultGuideGui {
                 None => egui::RichText::new(""),
             });
         });
+        if let Some(diff) = &self.diff {
+            let mut open = true;
+            egui::Window::new("Diff")
+                .open(&mut open)
+                .default_width(700.0)
+                .show(ctx, |ui| show_diff(ui, diff));
+            if !open {
+                self.diff = None;
+            }
+        }
         if self.waiting_for_response > 0 {
             ctx.request_repaint();
         }
     }
 }
+
+/// A flaky test case as returned by the server, only the name is of interest
+#[derive(Deserialize)]
+struct FlakyTestCase {
+    name: String,
+}
+
+/// Header of a report with badges if its test case is flaky or it failed
+/// unlike the baseline of its project.
+fn report_header(ui: &egui::Ui, report: &Report, flaky: bool) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    let format = egui::TextFormat {
+        font_id: egui::TextStyle::Body.resolve(ui.style()),
+        color: ui.visuals().text_color(),
+        ..Default::default()
+    };
+    job.append(&report.to_string(), 0.0, format.clone());
+    if report.baseline_status == Some(BaselineStatus::NewFailure) {
+        job.append(
+            " new failure ",
+            8.0,
+            egui::TextFormat {
+                color: egui::Color32::WHITE,
+                background: egui::Color32::from_rgb(200, 40, 40),
+                ..format.clone()
+            },
+        );
+    }
+    if flaky {
+        job.append(
+            " flaky ",
+            8.0,
+            egui::TextFormat {
+                color: egui::Color32::BLACK,
+                background: egui::Color32::from_rgb(255, 190, 70),
+                ..format
+            },
+        );
+    }
+    job
+}
+
+/// Shows the steps with their sub-steps as collapsible entries.
+///
+/// The verdict of a step with sub-steps is the worst verdict of all of them.
+fn show_test_steps(ui: &mut egui::Ui, steps: &[TreeNode]) {
+    for node in steps {
+        let step = &node.step;
+        let duration = step
+            .duration_ms
+            .map(|ms| format!(" ({:.3} s)", ms as f64 / 1000.0))
+            .unwrap_or_default();
+        let label = format!(
+            "{} {} - {} - {}{}",
+            step.step_number,
+            step.name,
+            node.computed_verdict.as_str(),
+            step.date,
+            duration
+        );
+        if node.children.is_empty() {
+            ui.label(label);
+        } else {
+            egui::CollapsingHeader::new(label)
+                .id_source(step.id)
+                .show(ui, |ui| show_test_steps(ui, &node.children));
+        }
+        if let Some(details) = failure_details(step) {
+            ui.indent(("details", step.id), |ui| {
+                ui.label(egui::RichText::new(details).weak());
+            });
+        }
+    }
+}
+
+/// Triage entry being edited in the view of a report
+#[derive(Clone, Serialize)]
+struct TriageForm {
+    test_step_id: Option<i32>,
+    status: TriageStatus,
+    category: TriageCategory,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    ticket: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    comment: String,
+    author: String,
+}
+
+impl TriageForm {
+    fn new(author: String) -> Self {
+        Self {
+            test_step_id: None,
+            status: TriageStatus::Open,
+            category: TriageCategory::KnownIssue,
+            ticket: String::new(),
+            comment: String::new(),
+            author,
+        }
+    }
+}
+
+/// Shows the triage entries of a report and a form to add another one,
+/// returns whether the form was submitted.
+fn show_triage(
+    ui: &mut egui::Ui,
+    report_id: i32,
+    entries: &[Triage],
+    steps: Option<&Vec<TreeNode>>,
+    form: &mut TriageForm,
+) -> bool {
+    let mut failed_steps = Vec::new();
+    let mut pending: Vec<&TreeNode> = steps.into_iter().flatten().collect();
+    while let Some(node) = pending.pop() {
+        if node.step.verdict.is_failure() {
+            failed_steps.push(&node.step);
+        }
+        pending.extend(&node.children);
+    }
+    failed_steps.sort_by_key(|step| step.id);
+    let step_label = |test_step_id: Option<i32>| match test_step_id {
+        Some(id) => failed_steps
+            .iter()
+            .find(|step| step.id == id)
+            .map_or(format!("Step {id}"), |step| {
+                format!("{} {}", step.step_number, step.name)
+            }),
+        None => "Whole report".to_string(),
+    };
+
+    egui::CollapsingHeader::new("Triage")
+        .id_source(("triage", report_id))
+        .default_open(true)
+        .show(ui, |ui| {
+            for entry in entries {
+                let ticket = entry
+                    .ticket
+                    .as_deref()
+                    .map(|ticket| format!(" [{ticket}]"))
+                    .unwrap_or_default();
+                ui.label(format!(
+                    "{} - {} - {} - {}{} by {}",
+                    entry.date,
+                    step_label(entry.test_step_id),
+                    status_label(entry.status),
+                    category_label(entry.category),
+                    ticket,
+                    entry.author
+                ));
+                if let Some(comment) = &entry.comment {
+                    ui.indent(("triage_comment", entry.id), |ui| {
+                        ui.label(egui::RichText::new(comment).weak());
+                    });
+                }
+            }
+            egui::Grid::new(("triage_form", report_id))
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Step");
+                    egui::ComboBox::from_id_source(("triage_step", report_id))
+                        .selected_text(step_label(form.test_step_id))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut form.test_step_id, None, step_label(None));
+                            for step in &failed_steps {
+                                ui.selectable_value(
+                                    &mut form.test_step_id,
+                                    Some(step.id),
+                                    step_label(Some(step.id)),
+                                );
+                            }
+                        });
+                    ui.end_row();
+                    ui.label("Status");
+                    egui::ComboBox::from_id_source(("triage_status", report_id))
+                        .selected_text(status_label(form.status))
+                        .show_ui(ui, |ui| {
+                            for status in [TriageStatus::Open, TriageStatus::Resolved] {
+                                ui.selectable_value(&mut form.status, status, status_label(status));
+                            }
+                        });
+                    ui.end_row();
+                    ui.label("Category");
+                    egui::ComboBox::from_id_source(("triage_category", report_id))
+                        .selected_text(category_label(form.category))
+                        .show_ui(ui, |ui| {
+                            for category in [
+                                TriageCategory::KnownIssue,
+                                TriageCategory::BenchProblem,
+                                TriageCategory::RealDefect,
+                            ] {
+                                ui.selectable_value(
+                                    &mut form.category,
+                                    category,
+                                    category_label(category),
+                                );
+                            }
+                        });
+                    ui.end_row();
+                    ui.label("Ticket");
+                    ui.text_edit_singleline(&mut form.ticket);
+                    ui.end_row();
+                    ui.label("Comment");
+                    ui.text_edit_multiline(&mut form.comment);
+                    ui.end_row();
+                    ui.label("Author");
+                    ui.text_edit_singleline(&mut form.author);
+                    ui.end_row();
+                });
+            ui.add_enabled(
+                !form.author.trim().is_empty(),
+                egui::Button::new("Add triage"),
+            )
+            .clicked()
+        })
+        .body_returned
+        .unwrap_or(false)
+}
+
+fn status_label(status: TriageStatus) -> &'static str {
+    match status {
+        TriageStatus::Open => "open",
+        TriageStatus::Resolved => "resolved",
+    }
+}
+
+fn category_label(category: TriageCategory) -> &'static str {
+    match category {
+        TriageCategory::KnownIssue => "known issue",
+        TriageCategory::BenchProblem => "bench problem",
+        TriageCategory::RealDefect => "real defect",
+    }
+}
+
+/// Shows the steps of two reports side by side with their changes.
+fn show_diff(ui: &mut egui::Ui, diff: &ReportDiff) {
+    ui.label(format!("Old: {}", diff.old));
+    ui.label(format!("New: {}", diff.new));
+    if let Some(change) = diff.duration_change_ms {
+        ui.label(format!("Duration: {}", duration_change(change)));
+    }
+    let counts: Vec<_> = diff
+        .counts
+        .iter()
+        .map(|(change, count)| format!("{count} {}", change_label(*change)))
+        .collect();
+    ui.label(counts.join(", "));
+    ui.separator();
+
+    let side = |step: Option<&TestStep>| match step {
+        Some(step) => format!(
+            "{}{}",
+            step.verdict.as_str(),
+            step.duration_ms
+                .map(|ms| format!(" ({:.3} s)", ms as f64 / 1000.0))
+                .unwrap_or_default()
+        ),
+        None => "-".to_string(),
+    };
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("diff")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                for header in ["Step", "Old", "New", "Change", "Duration"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for step in &diff.steps {
+                    ui.label(format!(
+                        "{}{} {}",
+                        "    ".repeat(step.path.len()),
+                        step.step_number,
+                        step.name
+                    ));
+                    ui.label(side(step.old.as_ref()));
+                    ui.label(side(step.new.as_ref()));
+                    ui.label(
+                        egui::RichText::new(change_label(step.change))
+                            .color(change_color(step.change)),
+                    );
+                    ui.label(
+                        step.duration_change_ms
+                            .map(duration_change)
+                            .unwrap_or_default(),
+                    );
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+fn change_label(change: StepChange) -> &'static str {
+    match change {
+        StepChange::Added => "added",
+        StepChange::Removed => "removed",
+        StepChange::NewlyFailing => "newly failing",
+        StepChange::Fixed => "fixed",
+        StepChange::Changed => "changed",
+        StepChange::Unchanged => "unchanged",
+    }
+}
+
+fn change_color(change: StepChange) -> egui::Color32 {
+    match change {
+        StepChange::Added => egui::Color32::LIGHT_BLUE,
+        StepChange::Removed => egui::Color32::GRAY,
+        StepChange::NewlyFailing => egui::Color32::RED,
+        StepChange::Fixed => egui::Color32::GREEN,
+        StepChange::Changed => egui::Color32::YELLOW,
+        StepChange::Unchanged => egui::Color32::DARK_GRAY,
+    }
+}
+
+fn duration_change(ms: i64) -> String {
+    format!("{:+.3} s", ms as f64 / 1000.0)
+}
+
+/// Describes why a step got its verdict, `None` if nothing is known.
+fn failure_details(step: &TestStep) -> Option<String> {
+    let unit = step.unit.as_deref().unwrap_or_default();
+    let value = |value: &str| format!("{value} {unit}").trim_end().to_string();
+    let details: Vec<_> = [
+        step.failure_category
+            .map(|category| category.as_str().to_string()),
+        step.message.clone(),
+        step.expected
+            .as_deref()
+            .map(|expected| format!("expected {}", value(expected))),
+        step.actual
+            .as_deref()
+            .map(|actual| format!("actual {}", value(actual))),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    (!details.is_empty()).then(|| details.join(", "))
+}
@@ -0,0 +1,320 @@
+//! Endpoints for files attached to reports and test steps.
+
+use entities::{prelude::*, *};
+
+use crate::blob_store::BlobStore;
+use crate::error::{ErrorDetails, RgError, WithStatusCode};
+use crate::extract::Path;
+use axum::{
+    body::Bytes,
+    extract::{
+        multipart::{MultipartError, MultipartRejection},
+        Multipart, State,
+    },
+    http::{header, StatusCode},
+    Json,
+};
+use eyre::eyre;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use sea_orm::{
+    sea_query::SimpleExpr, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// MIME type of uploaded files that do not specify one
+const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+
+/// Name of the form field selecting the test step the files belong to
+const TEST_STEP_ID_FIELD: &str = "test_step_id";
+
+/// Uploads hold it shared from writing their blobs until the attachments
+/// referencing them are stored, the cleanup exclusively, so that it never
+/// deletes a blob an upload is about to reference.
+static BLOB_LOCK: RwLock<()> = RwLock::const_new(());
+
+/// Characters that have to be percent encoded in an RFC 5987 `filename*`,
+/// i.e. all but `attr-char`
+const FILENAME_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+struct UploadedFile {
+    filename: String,
+    mime_type: String,
+    content: Bytes,
+}
+
+/// Stores every file of a `multipart/form-data` upload as attachment of the
+/// report, or of one of its test steps if the form contains a `test_step_id`.
+pub(crate) async fn add_attachments(
+    Path(report_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+    State(blobs): State<Arc<dyn BlobStore>>,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<Json<Vec<attachment::Model>>, RgError> {
+    let mut multipart = multipart.map_err(|rejection| {
+        RgError::from(eyre!(rejection.body_text())).with_status_code(StatusCode::BAD_REQUEST)
+    })?;
+    let report = crate::find_report(&db, report_id).await?;
+
+    let mut test_step_id = None;
+    let mut files = Vec::new();
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == TEST_STEP_ID_FIELD {
+            let text = field.text().await.map_err(multipart_error)?;
+            test_step_id = Some(text.trim().parse::<i32>().map_err(|e| {
+                RgError::from(eyre!("Invalid test step id {text}: {e}"))
+                    .with_status_code(StatusCode::BAD_REQUEST)
+                    .with_details(field_details(TEST_STEP_ID_FIELD))
+            })?);
+            continue;
+        }
+        let Some(filename) = field.file_name().map(str::to_string) else {
+            return Err(RgError::from(eyre!("Form field {name} is not a file"))
+                .with_status_code(StatusCode::BAD_REQUEST)
+                .with_details(field_details(&name)));
+        };
+        let mime_type = field
+            .content_type()
+            .unwrap_or(DEFAULT_MIME_TYPE)
+            .to_string();
+        let content = field.bytes().await.map_err(multipart_error)?;
+        files.push(UploadedFile {
+            filename,
+            mime_type,
+            content,
+        });
+    }
+    if files.is_empty() {
+        return Err(eyre!("Upload does not contain any file"))
+            .with_status_code(StatusCode::BAD_REQUEST);
+    }
+    if let Some(test_step_id) = test_step_id {
+        let step = TestStep::find_by_id(test_step_id).one(&db).await?;
+        if step.map(|step| step.report_id) != Some(report.id) {
+            return Err(RgError::from(eyre!(
+                "Could not find test step with id {} in report {}!",
+                test_step_id,
+                report.id
+            ))
+            .with_status_code(StatusCode::BAD_REQUEST)
+            .with_details(field_details(TEST_STEP_ID_FIELD)));
+        }
+    }
+
+    // Blobs are written first, a failed insert only leaves an unreferenced
+    // blob behind which is reused by the next upload of the same content.
+    let _guard = BLOB_LOCK.read().await;
+    let mut models = Vec::with_capacity(files.len());
+    for file in files {
+        let sha256 = hex::encode(Sha256::digest(&file.content));
+        let size = file.content.len() as i64;
+        blobs.put(&sha256, file.content).await?;
+        models.push(attachment::ActiveModel {
+            report_id: ActiveValue::Set(report.id),
+            test_step_id: ActiveValue::Set(test_step_id),
+            filename: ActiveValue::Set(file.filename),
+            mime_type: ActiveValue::Set(file.mime_type),
+            size: ActiveValue::Set(size),
+            sha256: ActiveValue::Set(sha256),
+            ..Default::default()
+        });
+    }
+    let attachments = db
+        .transaction(|txn| {
+            Box::pin(async move {
+                let mut ids = Vec::with_capacity(models.len());
+                for model in models {
+                    ids.push(Attachment::insert(model).exec(txn).await?.last_insert_id);
+                }
+                Attachment::find()
+                    .filter(attachment::Column::Id.is_in(ids))
+                    .order_by_asc(attachment::Column::Id)
+                    .all(txn)
+                    .await
+            })
+        })
+        .await?;
+    Ok(Json(attachments))
+}
+
+/// Keeps the status of the multipart error, e.g. `413 Payload Too Large` if
+/// the upload exceeds the size limit.
+fn multipart_error(error: MultipartError) -> RgError {
+    RgError::from(eyre!(error.body_text())).with_status_code(error.status())
+}
+
+fn field_details(field: &str) -> ErrorDetails {
+    ErrorDetails {
+        field: Some(field.to_string()),
+    }
+}
+
+/// Lists the attachments of a report including those of its test steps.
+pub(crate) async fn get_attachments_for_report(
+    Path(report_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<attachment::Model>>, RgError> {
+    let report = crate::find_report(&db, report_id).await?;
+    Ok(Json(
+        Attachment::find()
+            .filter(attachment::Column::ReportId.eq(report.id))
+            .order_by_asc(attachment::Column::Id)
+            .all(&db)
+            .await?,
+    ))
+}
+
+pub(crate) async fn get_attachments_for_teststep(
+    Path(teststep_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<attachment::Model>>, RgError> {
+    let ts = crate::find_teststep(&db, teststep_id).await?;
+    Ok(Json(
+        Attachment::find()
+            .filter(attachment::Column::TestStepId.eq(ts.id))
+            .order_by_asc(attachment::Column::Id)
+            .all(&db)
+            .await?,
+    ))
+}
+
+async fn find_by_id<C: ConnectionTrait>(
+    db: &C,
+    attachment_id: i32,
+) -> Result<attachment::Model, RgError> {
+    Attachment::find_by_id(attachment_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| eyre!("Could not find attachment with id {attachment_id}!"))
+        .with_status_code(StatusCode::NOT_FOUND)
+}
+
+pub(crate) async fn get_single_attachment(
+    Path(attachment_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<attachment::Model>, RgError> {
+    Ok(Json(find_by_id(&db, attachment_id).await?))
+}
+
+/// Downloads the content of an attachment with its original filename.
+pub(crate) async fn download_attachment(
+    Path(attachment_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+    State(blobs): State<Arc<dyn BlobStore>>,
+) -> Result<([(header::HeaderName, String); 2], Bytes), RgError> {
+    let attachment = find_by_id(&db, attachment_id).await?;
+    let content = blobs
+        .get(&attachment.sha256)
+        .await?
+        .ok_or_else(|| eyre!("Content of attachment {} is missing", attachment.id))?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.mime_type),
+            (
+                header::CONTENT_DISPOSITION,
+                content_disposition(&attachment.filename),
+            ),
+        ],
+        content,
+    ))
+}
+
+/// Deletes the attachments matching `condition` and returns the keys of their
+/// blobs, see [`delete_unused_blobs`].
+pub(crate) async fn delete_where<C: ConnectionTrait>(
+    db: &C,
+    condition: SimpleExpr,
+) -> Result<Vec<String>, DbErr> {
+    let attachments = Attachment::find().filter(condition.clone()).all(db).await?;
+    Attachment::delete_many().filter(condition).exec(db).await?;
+    Ok(attachments
+        .into_iter()
+        .map(|attachment| attachment.sha256)
+        .collect())
+}
+
+/// Deletes the blobs that are no longer referenced by any attachment.
+///
+/// This is only done after the attachments were deleted for good, failures
+/// are logged since the attachments themselves are already gone.
+pub(crate) async fn delete_unused_blobs(
+    db: &DatabaseConnection,
+    blobs: &dyn BlobStore,
+    mut keys: Vec<String>,
+) {
+    keys.sort();
+    keys.dedup();
+    let _guard = BLOB_LOCK.write().await;
+    for key in keys {
+        let in_use = Attachment::find()
+            .filter(attachment::Column::Sha256.eq(key.as_str()))
+            .count(db)
+            .await;
+        let result = match in_use {
+            Ok(0) => blobs.delete(&key).await,
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Could not delete blob {key}: {e:#}");
+        }
+    }
+}
+
+/// Builds the `Content-Disposition` header of a download.
+///
+/// Control characters, quotes and backslashes would end or break the quoted
+/// `filename` and are dropped. Non-ASCII names are replaced by `_` there and
+/// additionally given in full as RFC 5987 `filename*`.
+fn content_disposition(filename: &str) -> String {
+    let filename: String = filename
+        .chars()
+        .filter(|c| !c.is_control() && !matches!(c, '"' | '\\'))
+        .collect();
+    let ascii: String = filename
+        .chars()
+        .map(|c| if c.is_ascii() { c } else { '_' })
+        .collect();
+    if ascii == filename {
+        format!("attachment; filename=\"{filename}\"")
+    } else {
+        let encoded = utf8_percent_encode(&filename, FILENAME_ENCODE_SET);
+        format!("attachment; filename=\"{ascii}\"; filename*=UTF-8''{encoded}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_disposition() {
+        assert_eq!(
+            content_disposition("test.log"),
+            "attachment; filename=\"test.log\""
+        );
+        assert_eq!(
+            content_disposition("a\"b\\c\r\nd\te\u{7f}.log"),
+            "attachment; filename=\"abcde.log\""
+        );
+        assert_eq!(
+            content_disposition("Spannung µV.csv"),
+            "attachment; filename=\"Spannung _V.csv\"; filename*=UTF-8''Spannung%20%C2%B5V.csv"
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! Baselines of projects, which new reports are compared with to tell new
+//! failures from known ones.
+
+use entities::{prelude::*, *};
+
+use crate::error::{ErrorDetails, RgError, WithStatusCode};
+use crate::extract::{self, Path, Query};
+use axum::{extract::State, http::StatusCode, Json};
+use eyre::eyre;
+use sea_orm::{
+    sea_query::{Expr, Query as SqlQuery},
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection,
+    DbErr, EntityTrait, ModelTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Either a single report or a test run of the project
+#[derive(Deserialize)]
+pub(crate) struct NewBaseline {
+    #[serde(default)]
+    report_id: Option<i32>,
+    #[serde(default)]
+    test_run_id: Option<i32>,
+}
+
+/// Sets the baseline of a project, which replaces any previous one.
+///
+/// Existing reports keep their `baseline_status` until they or their steps
+/// change, only those are compared with the new baseline.
+pub(crate) async fn set_baseline(
+    Path(project_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+    baseline: String,
+) -> Result<Json<project::Model>, RgError> {
+    let baseline: NewBaseline = extract::json(&baseline)?;
+    let project = crate::projects::find_by_id(&db, project_id).await?;
+    match (baseline.report_id, baseline.test_run_id) {
+        (Some(report_id), None) => {
+            let report = Report::find_by_id(report_id).one(&db).await?;
+            if report.and_then(|report| report.project_id) != Some(project.id) {
+                return Err(not_in_project("report", report_id, "report_id"));
+            }
+        }
+        (None, Some(test_run_id)) => {
+            let run = TestRun::find_by_id(test_run_id).one(&db).await?;
+            if run.map(|run| run.project_id) != Some(project.id) {
+                return Err(not_in_project("test run", test_run_id, "test_run_id"));
+            }
+        }
+        _ => {
+            return Err(eyre!("The baseline is either a report_id or a test_run_id"))
+                .with_status_code(StatusCode::BAD_REQUEST)
+        }
+    }
+
+    let mut project_model: project::ActiveModel = project.into();
+    project_model.baseline_report_id = ActiveValue::Set(baseline.report_id);
+    project_model.baseline_test_run_id = ActiveValue::Set(baseline.test_run_id);
+    Ok(Json(project_model.update(&db).await?))
+}
+
+fn not_in_project(kind: &str, id: i32, field: &str) -> RgError {
+    RgError::from(eyre!("Could not find {kind} with id {id} in the project!"))
+        .with_status_code(StatusCode::BAD_REQUEST)
+        .with_details(ErrorDetails {
+            field: Some(field.to_string()),
+        })
+}
+
+/// Removes the baseline of a project, reports uploaded afterwards are no
+/// longer compared.
+pub(crate) async fn delete_baseline(
+    Path(project_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<project::Model>, RgError> {
+    let project = crate::projects::find_by_id(&db, project_id).await?;
+    let mut project_model: project::ActiveModel = project.into();
+    project_model.baseline_report_id = ActiveValue::Set(None);
+    project_model.baseline_test_run_id = ActiveValue::Set(None);
+    Ok(Json(project_model.update(&db).await?))
+}
+
+/// Removes the baseline of the projects whose baseline is `id` in `column`,
+/// e.g. before deleting the report or test run.
+pub(crate) async fn unset<C: ConnectionTrait>(
+    db: &C,
+    column: project::Column,
+    id: i32,
+) -> Result<(), DbErr> {
+    Project::update_many()
+        .col_expr(column, Expr::value(Option::<i32>::None))
+        .filter(column.eq(id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Finds the report of the baseline with the same name as `report`, the most
+/// recent one if the baseline is a test run.
+async fn find_baseline_report<C: ConnectionTrait>(
+    db: &C,
+    project: &project::Model,
+    report: &report::Model,
+) -> Result<Option<report::Model>, DbErr> {
+    let select = match (project.baseline_report_id, project.baseline_test_run_id) {
+        (Some(report_id), _) => Report::find_by_id(report_id),
+        (None, Some(test_run_id)) => {
+            Report::find().filter(report::Column::TestRunId.eq(test_run_id))
+        }
+        (None, None) => return Ok(None),
+    };
+    select
+        .filter(report::Column::Name.eq(report.name.as_str()))
+        .order_by_desc(report::Column::Date)
+        .order_by_desc(report::Column::Id)
+        .one(db)
+        .await
+}
+
+/// Compares a report and its test steps with the baseline of its project and
+/// stores the outcome in their `baseline_status`.
+///
+/// Reports without a baseline and the reports of the baseline itself get no
+/// status.
+pub(crate) async fn compare<C: ConnectionTrait>(
+    db: &C,
+    report: report::Model,
+) -> Result<report::Model, DbErr> {
+    let project = match report.project_id {
+        Some(project_id) => Project::find_by_id(project_id).one(db).await?,
+        None => None,
+    };
+    let is_compared = project.as_ref().is_some_and(|project| {
+        let is_baseline = project.baseline_report_id == Some(report.id)
+            || (project.baseline_test_run_id.is_some()
+                && project.baseline_test_run_id == report.test_run_id);
+        (project.baseline_report_id.is_some() || project.baseline_test_run_id.is_some())
+            && !is_baseline
+    });
+
+    let steps = report.find_related(TestStep).all(db).await?;
+    let (report_status, step_statuses) = match project.filter(|_| is_compared) {
+        Some(project) => {
+            let baseline = find_baseline_report(db, &project, &report).await?;
+            let baseline_steps = match &baseline {
+                Some(baseline) => baseline.find_related(TestStep).all(db).await?,
+                None => Vec::new(),
+            };
+            let report_status =
+                BaselineStatus::between(baseline.map(|baseline| baseline.verdict), report.verdict);
+            let step_statuses = diff::diff_steps(baseline_steps, steps)
+                .into_iter()
+                .filter_map(|step| {
+                    let new = step.new?;
+                    let status =
+                        BaselineStatus::between(step.old.map(|old| old.verdict), new.verdict);
+                    Some((new, status))
+                })
+                .collect();
+            (report_status, step_statuses)
+        }
+        None => (
+            None,
+            steps
+                .into_iter()
+                .map(|step| (step, None))
+                .collect::<Vec<_>>(),
+        ),
+    };
+
+    let mut changed: HashMap<Option<BaselineStatus>, Vec<i32>> = HashMap::new();
+    for (step, status) in step_statuses {
+        if step.baseline_status != status {
+            changed.entry(status).or_default().push(step.id);
+        }
+    }
+    for (status, ids) in changed {
+        TestStep::update_many()
+            .col_expr(test_step::Column::BaselineStatus, Expr::value(status))
+            .filter(test_step::Column::Id.is_in(ids))
+            .exec(db)
+            .await?;
+    }
+
+    if report.baseline_status == report_status {
+        return Ok(report);
+    }
+    let mut report_model: report::ActiveModel = report.into();
+    report_model.baseline_status = ActiveValue::Set(report_status);
+    report_model.update(db).await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RegressionQuery {
+    /// Only consider the reports of this test run
+    test_run_id: Option<i32>,
+}
+
+/// The most recent report of a test case, which failed where the baseline
+/// did not
+#[derive(Serialize)]
+pub(crate) struct Regression {
+    #[serde(flatten)]
+    report: report::Model,
+    /// Test steps that fail but did not fail in the baseline
+    new_failures: Vec<test_step::Model>,
+}
+
+/// Lists the test cases of a project whose most recent report is a new
+/// failure compared with the baseline, either as a whole or in any of its
+/// test steps.
+pub(crate) async fn get_regressions(
+    Path(project_id): Path<i32>,
+    Query(query): Query<RegressionQuery>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<Regression>>, RgError> {
+    let project = crate::projects::find_by_id(&db, project_id).await?;
+    let mut select = Report::find().filter(report::Column::ProjectId.eq(project.id));
+    if let Some(test_run_id) = query.test_run_id {
+        select = select.filter(report::Column::TestRunId.eq(test_run_id));
+    }
+
+    // The most recent report of each test case comes first.
+    let mut latest = HashMap::new();
+    let reports: Vec<(i32, String)> = select
+        .clone()
+        .select_only()
+        .column(report::Column::Id)
+        .column(report::Column::Name)
+        .order_by_desc(report::Column::Date)
+        .order_by_desc(report::Column::Id)
+        .into_tuple()
+        .all(&db)
+        .await?;
+    for (id, name) in reports {
+        latest.entry(name).or_insert(id);
+    }
+
+    let failing_steps = SqlQuery::select()
+        .column(test_step::Column::ReportId)
+        .from(TestStep)
+        .and_where(test_step::Column::BaselineStatus.eq(BaselineStatus::NewFailure))
+        .to_owned();
+    let reports: Vec<_> = select
+        .filter(
+            Condition::any()
+                .add(report::Column::BaselineStatus.eq(BaselineStatus::NewFailure))
+                .add(report::Column::Id.in_subquery(failing_steps)),
+        )
+        .order_by_asc(report::Column::Name)
+        .all(&db)
+        .await?
+        .into_iter()
+        .filter(|report| latest.get(&report.name) == Some(&report.id))
+        .collect();
+
+    let reports = crate::report_metadata::load(&db, reports).await?;
+    let mut regressions = Vec::with_capacity(reports.len());
+    for report in reports {
+        let new_failures = report
+            .find_related(TestStep)
+            .filter(test_step::Column::BaselineStatus.eq(BaselineStatus::NewFailure))
+            .order_by_asc(test_step::Column::Id)
+            .all(&db)
+            .await?
+            .into_iter()
+            .map(test_step::Model::with_original_offset)
+            .collect();
+        regressions.push(Regression {
+            report: report.with_original_offset(),
+            new_failures,
+        });
+    }
+    Ok(Json(regressions))
+}
@@ -0,0 +1,160 @@
+//! Storage for the content of attachments.
+//!
+//! Blobs are addressed by the hex encoded SHA-256 of their content, so
+//! attachments with the same content share a single blob.
+
+use axum::body::Bytes;
+use eyre::WrapErr;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+
+#[async_trait::async_trait]
+pub(crate) trait BlobStore: Send + Sync {
+    /// Stores `content` under `key`, an existing blob with that key is kept.
+    async fn put(&self, key: &str, content: Bytes) -> eyre::Result<()>;
+
+    /// Returns the blob stored under `key` or `None` if there is none.
+    async fn get(&self, key: &str) -> eyre::Result<Option<Bytes>>;
+
+    /// Removes the blob stored under `key`, missing blobs are ignored.
+    async fn delete(&self, key: &str) -> eyre::Result<()>;
+}
+
+/// Keeps every blob in a file below a directory on the local file system.
+pub(crate) struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Blobs are spread over subdirectories named after the first two
+    /// characters of their key to keep the directories small.
+    fn path(&self, key: &str) -> eyre::Result<PathBuf> {
+        if key.len() < 3 || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(eyre::eyre!("Invalid blob key {key}"));
+        }
+        Ok(self.root.join(&key[..2]).join(key))
+    }
+}
+
+#[async_trait::async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, content: Bytes) -> eyre::Result<()> {
+        let path = self.path(key)?;
+        if tokio::fs::try_exists(&path).await? {
+            return Ok(());
+        }
+        let dir = path.parent().expect("blob paths have a parent");
+        tokio::fs::create_dir_all(dir)
+            .await
+            .wrap_err_with(|| format!("Could not create {}", dir.display()))?;
+        // Write to a temporary file first so that readers never see a
+        // partially written blob, every upload gets its own one since the
+        // same content may be uploaded concurrently.
+        let dir = dir.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let mut file = tempfile::NamedTempFile::new_in(&dir)?;
+            file.write_all(&content)?;
+            file.persist(&path)?;
+            Ok::<_, std::io::Error>(())
+        })
+        .await?
+        .wrap_err_with(|| format!("Could not write blob {key}"))
+    }
+
+    async fn get(&self, key: &str) -> eyre::Result<Option<Bytes>> {
+        let path = self.path(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(content) => Ok(Some(content.into())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).wrap_err_with(|| format!("Could not read {}", path.display())),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> eyre::Result<()> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                Err(e).wrap_err_with(|| format!("Could not delete {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Keeps all blobs in memory, for tests that do not care about the file system
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MemoryBlobStore {
+    blobs: std::sync::Mutex<std::collections::HashMap<String, Bytes>>,
+}
+
+#[cfg(test)]
+impl MemoryBlobStore {
+    pub fn len(&self) -> usize {
+        self.blobs.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl BlobStore for MemoryBlobStore {
+    async fn put(&self, key: &str, content: Bytes) -> eyre::Result<()> {
+        self.blobs
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_insert(content);
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> eyre::Result<Option<Bytes>> {
+        Ok(self.blobs.lock().unwrap().get(key).cloned())
+    }
+
+    async fn delete(&self, key: &str) -> eyre::Result<()> {
+        self.blobs.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_blob_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalBlobStore::new(dir.path());
+
+        assert_eq!(store.get("abcdef").await.unwrap(), None);
+        store.put("abcdef", Bytes::from("content")).await.unwrap();
+        assert!(dir.path().join("ab").join("abcdef").is_file());
+        assert_eq!(
+            store.get("abcdef").await.unwrap(),
+            Some(Bytes::from("content"))
+        );
+
+        store.delete("abcdef").await.unwrap();
+        assert_eq!(store.get("abcdef").await.unwrap(), None);
+        store.delete("abcdef").await.unwrap();
+
+        assert!(store.put("../etc", Bytes::new()).await.is_err());
+
+        // Concurrent uploads of the same content do not interfere
+        let (first, second) = tokio::join!(
+            store.put("123456", Bytes::from("content")),
+            store.put("123456", Bytes::from("content"))
+        );
+        first.unwrap();
+        second.unwrap();
+        let files: Vec<_> = std::fs::read_dir(dir.path().join("12"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, ["123456"]);
+    }
+}
@@ -1,23 +1,69 @@
 use crate::StatusCode;
-use axum::response::{IntoResponse, Response};
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use eyre::eyre;
+use serde::Serialize;
 
-pub(crate) struct RgError(eyre::Error, StatusCode);
+pub(crate) struct RgError(eyre::Error, StatusCode, Option<ErrorDetails>);
+
+/// Problem document returned for every failed request
+#[derive(Serialize)]
+struct ErrorBody {
+    /// HTTP status code
+    code: u16,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<ErrorDetails>,
+}
+
+/// Additional information about what exactly was wrong with the request
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct ErrorDetails {
+    /// Path of the field that could not be parsed, e.g. `test_steps[1].verdict`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+}
 
 pub(crate) trait WithStatusCode {
     type Output;
     fn with_status_code(self, code: StatusCode) -> Self::Output;
 }
 
+impl RgError {
+    pub fn with_details(self, details: ErrorDetails) -> Self {
+        Self(self.0, self.1, Some(details))
+    }
+}
+
 impl IntoResponse for RgError {
     fn into_response(self) -> Response {
-        (self.1, self.0.to_string()).into_response()
+        // Server errors may reveal internals like SQL statements or paths,
+        // the client only learns that something went wrong.
+        let message = if self.1.is_server_error() {
+            tracing::error!("{:#}", self.0);
+            self.1
+                .canonical_reason()
+                .unwrap_or("Server error")
+                .to_string()
+        } else {
+            self.0.to_string()
+        };
+        let body = ErrorBody {
+            code: self.1.as_u16(),
+            message,
+            details: self.2,
+        };
+        (self.1, Json(body)).into_response()
     }
 }
 
 impl WithStatusCode for RgError {
     type Output = RgError;
     fn with_status_code(self, code: StatusCode) -> Self::Output {
-        Self(self.0, code)
+        Self(self.0, code, self.2)
     }
 }
 
@@ -26,7 +72,7 @@ where
     E: Into<eyre::Error>,
 {
     fn from(err: E) -> Self {
-        Self(err.into(), StatusCode::INTERNAL_SERVER_ERROR)
+        Self(err.into(), StatusCode::INTERNAL_SERVER_ERROR, None)
     }
 }
 
@@ -39,3 +85,21 @@ where
         self.map_err(|e| RgError::from(e).with_status_code(code))
     }
 }
+
+/// Replaces the empty response axum sends for a method a route does not
+/// support with an error document, keeping its `Allow` header.
+pub(crate) async fn method_not_allowed(response: Response) -> Response {
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED
+        || response.headers().contains_key(header::CONTENT_TYPE)
+    {
+        return response;
+    }
+    let allow = response.headers().get(header::ALLOW).cloned();
+    let mut error = RgError::from(eyre!("Method not allowed"))
+        .with_status_code(StatusCode::METHOD_NOT_ALLOWED)
+        .into_response();
+    if let Some(allow) = allow {
+        error.headers_mut().insert(header::ALLOW, allow);
+    }
+    error
+}
@@ -0,0 +1,125 @@
+//! Extractors and body parsers that report failures as [`RgError`] instead of
+//! axum's plain-text rejections.
+
+use crate::error::{ErrorDetails, RgError, WithStatusCode};
+use axum::{
+    async_trait,
+    extract::{path::ErrorKind, rejection::PathRejection, FromRequestParts},
+    http::{request::Parts, StatusCode},
+};
+use eyre::eyre;
+use serde::{
+    de::{DeserializeOwned, Error, Unexpected},
+    Deserialize, Deserializer,
+};
+
+/// Replacement for [`axum::extract::Path`]
+pub(crate) struct Path<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Path<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = RgError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match axum::extract::Path::<T>::from_request_parts(parts, state).await {
+            Ok(axum::extract::Path(value)) => Ok(Self(value)),
+            Err(rejection) => {
+                let field = match &rejection {
+                    PathRejection::FailedToDeserializePathParams(e) => match e.kind() {
+                        ErrorKind::ParseErrorAtKey { key, .. }
+                        | ErrorKind::InvalidUtf8InPathParam { key } => Some(key.clone()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                // The rejection messages mention the raw path segment, which
+                // is more helpful than serde's message for a single integer.
+                Err(RgError::from(eyre!(rejection.body_text()))
+                    .with_status_code(rejection.status())
+                    .with_details(ErrorDetails { field }))
+            }
+        }
+    }
+}
+
+/// Replacement for [`axum::extract::Query`]
+pub(crate) struct Query<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Query<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = RgError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let query = parts.uri.query().unwrap_or_default();
+        let deserializer =
+            serde_urlencoded::Deserializer::new(form_urlencoded::parse(query.as_bytes()));
+        serde_path_to_error::deserialize(deserializer)
+            .map(Self)
+            .map_err(parse_error)
+    }
+}
+
+/// Parses a JSON request body
+pub(crate) fn json<T: DeserializeOwned>(body: &str) -> Result<T, RgError> {
+    let mut deserializer = serde_json::Deserializer::from_str(body);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(parse_error)?;
+    deserializer
+        .end()
+        .with_status_code(StatusCode::BAD_REQUEST)?;
+    Ok(value)
+}
+
+/// Deserializes a nullable field of a patch, so that a missing field stays
+/// `None` while `null` becomes `Some(None)` to clear the value.
+///
+/// Has to be combined with `#[serde(default)]`.
+pub(crate) fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Deserializes an optional duration in milliseconds, which must not be
+/// negative.
+pub(crate) fn duration_ms<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let duration_ms = Option::<i64>::deserialize(deserializer)?;
+    match duration_ms {
+        Some(ms) if ms < 0 => Err(D::Error::invalid_value(
+            Unexpected::Signed(ms),
+            &"a duration of at least 0 ms",
+        )),
+        _ => Ok(duration_ms),
+    }
+}
+
+/// [`duration_ms`] for patches, see [`nullable`]
+pub(crate) fn nullable_duration_ms<'de, D>(deserializer: D) -> Result<Option<Option<i64>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    duration_ms(deserializer).map(Some)
+}
+
+fn parse_error<E>(error: serde_path_to_error::Error<E>) -> RgError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let field = error.path().to_string();
+    let field = (field != ".").then_some(field);
+    RgError::from(eyre!("{}", error.inner()))
+        .with_status_code(StatusCode::BAD_REQUEST)
+        .with_details(ErrorDetails { field })
+}
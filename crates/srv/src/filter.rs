@@ -0,0 +1,484 @@
+//! Query parameters for filtering, sorting and paginating the list endpoints.
+
+use entities::{prelude::*, *};
+
+use sea_orm::{
+    prelude::DateTimeWithTimeZone,
+    sea_query::{Expr, Func, IntoColumnRef, LikeExpr, Query, SimpleExpr},
+    ColumnTrait, Condition, EntityTrait, JoinType, Order, QueryFilter, QueryOrder, QuerySelect,
+    QueryTrait, RelationTrait, Select,
+};
+use serde::Deserialize;
+
+/// Name of the header containing the number of matching items before pagination
+pub(crate) const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Asc => Order::Asc,
+            SortOrder::Desc => Order::Desc,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ReportSort {
+    #[default]
+    Id,
+    Date,
+    Project,
+    Name,
+    DurationMs,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ReportFilter {
+    pub project: Option<String>,
+    pub verdict: Option<Verdict>,
+    /// Substring of the name or a glob pattern with `*` and `?`
+    pub name: Option<String>,
+    pub from: Option<DateTimeWithTimeZone>,
+    pub to: Option<DateTimeWithTimeZone>,
+    /// Only items that took at least this long, e.g. to find slow steps
+    pub min_duration_ms: Option<i64>,
+    pub max_duration_ms: Option<i64>,
+    pub test_run_id: Option<i32>,
+    pub tag: Option<String>,
+    /// Only failed reports that are neither triaged as a whole nor in all
+    /// their failed steps
+    #[serde(default)]
+    pub untriaged: bool,
+    /// Required property values, given as `prop.KEY=VALUE`, see
+    /// [`property_filters`]
+    #[serde(skip)]
+    pub properties: Vec<(String, String)>,
+    #[serde(default)]
+    pub sort: ReportSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+impl ReportFilter {
+    /// Query for all matching reports without pagination
+    pub fn select(&self) -> Select<Report> {
+        let column = match self.sort {
+            ReportSort::Id => report::Column::Id,
+            ReportSort::Date => report::Column::Date,
+            ReportSort::Project => report::Column::Project,
+            ReportSort::Name => report::Column::Name,
+            ReportSort::DurationMs => report::Column::DurationMs,
+        };
+        let select = Report::find()
+            .apply_if(self.project.clone(), |select, project| {
+                select.filter(report::Column::Project.eq(project))
+            })
+            .apply_if(self.verdict, |select, verdict| {
+                select.filter(report::Column::Verdict.eq(verdict))
+            })
+            .apply_if(self.name.as_deref(), |select, name| {
+                select.filter(name_matches((report::Entity, report::Column::Name), name))
+            })
+            .apply_if(self.from, |select, from| {
+                select.filter(report::Column::Date.gte(date::to_utc(from)))
+            })
+            .apply_if(self.to, |select, to| {
+                select.filter(report::Column::Date.lte(date::to_utc(to)))
+            })
+            .apply_if(self.min_duration_ms, |select, min| {
+                select.filter(report::Column::DurationMs.gte(min))
+            })
+            .apply_if(self.max_duration_ms, |select, max| {
+                select.filter(report::Column::DurationMs.lte(max))
+            })
+            .apply_if(self.test_run_id, |select, test_run_id| {
+                select.filter(report::Column::TestRunId.eq(test_run_id))
+            })
+            .apply_if(self.tag.clone(), |select, tag| {
+                select.filter(
+                    report::Column::Id.in_subquery(
+                        Query::select()
+                            .column(report_tag::Column::ReportId)
+                            .from(ReportTag)
+                            .and_where(report_tag::Column::Tag.eq(tag))
+                            .to_owned(),
+                    ),
+                )
+            })
+            .apply_if(self.untriaged.then_some(()), |select, ()| {
+                let failed_steps = Query::select()
+                    .column(test_step::Column::ReportId)
+                    .from(TestStep)
+                    .and_where(test_step::Column::Verdict.is_in(Verdict::FAILURES))
+                    .to_owned();
+                select
+                    .filter(report::Column::Verdict.is_in(Verdict::FAILURES))
+                    .filter(
+                        report::Column::Id.not_in_subquery(
+                            Query::select()
+                                .column(triage::Column::ReportId)
+                                .from(Triage)
+                                .and_where(triage::Column::TestStepId.is_null())
+                                .to_owned(),
+                        ),
+                    )
+                    .filter(
+                        Condition::any()
+                            .add(report::Column::Id.not_in_subquery(failed_steps.clone()))
+                            .add(
+                                report::Column::Id.in_subquery(
+                                    failed_steps
+                                        .clone()
+                                        .and_where(
+                                            test_step::Column::Id.not_in_subquery(
+                                                Query::select()
+                                                    .column(triage::Column::TestStepId)
+                                                    .from(Triage)
+                                                    .and_where(
+                                                        triage::Column::TestStepId.is_not_null(),
+                                                    )
+                                                    .to_owned(),
+                                            ),
+                                        )
+                                        .to_owned(),
+                                ),
+                            ),
+                    )
+            });
+        self.properties
+            .iter()
+            .fold(select, |select, (key, value)| {
+                select.filter(
+                    report::Column::Id.in_subquery(
+                        Query::select()
+                            .column(report_property::Column::ReportId)
+                            .from(ReportProperty)
+                            .and_where(report_property::Column::Key.eq(key.as_str()))
+                            .and_where(report_property::Column::Value.eq(value.as_str()))
+                            .to_owned(),
+                    ),
+                )
+            })
+            .order_by(column, self.order.into())
+            // Keeps pages stable if several reports share the sort value
+            .order_by(report::Column::Id, self.order.into())
+    }
+
+    pub fn paginate(&self, select: Select<Report>) -> Select<Report> {
+        paginate(select, self.limit, self.offset)
+    }
+}
+
+/// Prefix of the query parameters filtering reports by their properties
+const PROPERTY_PREFIX: &str = "prop.";
+
+/// Collects the `prop.KEY=VALUE` pairs of a query string, which cannot be
+/// described by a struct since the keys are arbitrary.
+pub(crate) fn property_filters(query: Option<&str>) -> Vec<(String, String)> {
+    form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .filter_map(|(name, value)| {
+            let key = name.strip_prefix(PROPERTY_PREFIX)?;
+            Some((key.to_string(), value.into_owned()))
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TestStepSort {
+    #[default]
+    Id,
+    Date,
+    Name,
+    StepNumber,
+    ReportId,
+    DurationMs,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct TestStepFilter {
+    /// Project of the report the steps belong to
+    pub project: Option<String>,
+    pub report_id: Option<i32>,
+    pub verdict: Option<Verdict>,
+    /// Substring of the name or a glob pattern with `*` and `?`
+    pub name: Option<String>,
+    /// Substring of the message or a glob pattern with `*` and `?`
+    pub message: Option<String>,
+    pub failure_category: Option<FailureCategory>,
+    pub from: Option<DateTimeWithTimeZone>,
+    pub to: Option<DateTimeWithTimeZone>,
+    /// Only items that took at least this long, e.g. to find slow steps
+    pub min_duration_ms: Option<i64>,
+    pub max_duration_ms: Option<i64>,
+    #[serde(default)]
+    pub sort: TestStepSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+impl TestStepFilter {
+    /// Query for all matching test steps without pagination
+    pub fn select(&self) -> Select<TestStep> {
+        let column = match self.sort {
+            TestStepSort::Id => test_step::Column::Id,
+            TestStepSort::Date => test_step::Column::Date,
+            TestStepSort::Name => test_step::Column::Name,
+            TestStepSort::StepNumber => test_step::Column::StepNumber,
+            TestStepSort::ReportId => test_step::Column::ReportId,
+            TestStepSort::DurationMs => test_step::Column::DurationMs,
+        };
+        TestStep::find()
+            .apply_if(self.project.clone(), |select, project| {
+                select
+                    .inner_join(Report)
+                    .filter(report::Column::Project.eq(project))
+            })
+            .apply_if(self.report_id, |select, report_id| {
+                select.filter(test_step::Column::ReportId.eq(report_id))
+            })
+            .apply_if(self.verdict, |select, verdict| {
+                select.filter(test_step::Column::Verdict.eq(verdict))
+            })
+            .apply_if(self.name.as_deref(), |select, name| {
+                select.filter(name_matches(
+                    (test_step::Entity, test_step::Column::Name),
+                    name,
+                ))
+            })
+            .apply_if(self.message.as_deref(), |select, message| {
+                select.filter(name_matches(
+                    (test_step::Entity, test_step::Column::Message),
+                    message,
+                ))
+            })
+            .apply_if(self.failure_category, |select, failure_category| {
+                select.filter(test_step::Column::FailureCategory.eq(failure_category))
+            })
+            .apply_if(self.from, |select, from| {
+                select.filter(test_step::Column::Date.gte(date::to_utc(from)))
+            })
+            .apply_if(self.to, |select, to| {
+                select.filter(test_step::Column::Date.lte(date::to_utc(to)))
+            })
+            .apply_if(self.min_duration_ms, |select, min| {
+                select.filter(test_step::Column::DurationMs.gte(min))
+            })
+            .apply_if(self.max_duration_ms, |select, max| {
+                select.filter(test_step::Column::DurationMs.lte(max))
+            })
+            .order_by(column, self.order.into())
+            .order_by(test_step::Column::Id, self.order.into())
+    }
+
+    pub fn paginate(&self, select: Select<TestStep>) -> Select<TestStep> {
+        paginate(select, self.limit, self.offset)
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct MeasurementFilter {
+    /// Name of the measurement
+    pub name: String,
+    /// Project of the reports the measurements were taken in
+    pub project: Option<String>,
+    /// Substring of the report name or a glob pattern with `*` and `?`
+    pub report_name: Option<String>,
+    pub from: Option<DateTimeWithTimeZone>,
+    pub to: Option<DateTimeWithTimeZone>,
+    /// Order by the date of the test steps
+    #[serde(default)]
+    pub order: SortOrder,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+impl MeasurementFilter {
+    /// Query for all matching measurements without pagination
+    pub fn select(&self) -> Select<Measurement> {
+        Measurement::find()
+            .inner_join(TestStep)
+            .join(JoinType::InnerJoin, test_step::Relation::Report.def())
+            .filter(measurement::Column::Name.eq(self.name.as_str()))
+            .apply_if(self.project.clone(), |select, project| {
+                select.filter(report::Column::Project.eq(project))
+            })
+            .apply_if(self.report_name.as_deref(), |select, name| {
+                select.filter(name_matches((report::Entity, report::Column::Name), name))
+            })
+            .apply_if(self.from, |select, from| {
+                select.filter(test_step::Column::Date.gte(date::to_utc(from)))
+            })
+            .apply_if(self.to, |select, to| {
+                select.filter(test_step::Column::Date.lte(date::to_utc(to)))
+            })
+            .order_by(test_step::Column::Date, self.order.into())
+            .order_by(measurement::Column::Id, self.order.into())
+    }
+
+    pub fn paginate(&self, select: Select<Measurement>) -> Select<Measurement> {
+        paginate(select, self.limit, self.offset)
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct TestRunFilter {
+    pub project: Option<String>,
+    pub trigger: Option<String>,
+    /// Only runs that started in this period
+    pub from: Option<DateTimeWithTimeZone>,
+    pub to: Option<DateTimeWithTimeZone>,
+    /// Order by the start of the runs
+    #[serde(default)]
+    pub order: SortOrder,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+impl TestRunFilter {
+    /// Query for all matching test runs without pagination
+    pub fn select(&self) -> Select<TestRun> {
+        TestRun::find()
+            .apply_if(self.project.clone(), |select, project| {
+                select
+                    .inner_join(Project)
+                    .filter(project::Column::Name.eq(project))
+            })
+            .apply_if(self.trigger.clone(), |select, trigger| {
+                select.filter(test_run::Column::Trigger.eq(trigger))
+            })
+            .apply_if(self.from, |select, from| {
+                select.filter(test_run::Column::Start.gte(date::to_utc(from)))
+            })
+            .apply_if(self.to, |select, to| {
+                select.filter(test_run::Column::Start.lte(date::to_utc(to)))
+            })
+            .order_by(test_run::Column::Start, self.order.into())
+            .order_by(test_run::Column::Id, self.order.into())
+    }
+
+    pub fn paginate(&self, select: Select<TestRun>) -> Select<TestRun> {
+        paginate(select, self.limit, self.offset)
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct TriageFilter {
+    /// Project of the triaged reports
+    pub project: Option<String>,
+    pub report_id: Option<i32>,
+    pub test_step_id: Option<i32>,
+    pub status: Option<TriageStatus>,
+    pub category: Option<TriageCategory>,
+    pub ticket: Option<String>,
+    pub author: Option<String>,
+    /// Only entries triaged in this period
+    pub from: Option<DateTimeWithTimeZone>,
+    pub to: Option<DateTimeWithTimeZone>,
+    /// Order by the date of the entries
+    #[serde(default)]
+    pub order: SortOrder,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+impl TriageFilter {
+    /// Query for all matching triage entries without pagination
+    pub fn select(&self) -> Select<Triage> {
+        Triage::find()
+            .apply_if(self.project.clone(), |select, project| {
+                select
+                    .inner_join(Report)
+                    .filter(report::Column::Project.eq(project))
+            })
+            .apply_if(self.report_id, |select, report_id| {
+                select.filter(triage::Column::ReportId.eq(report_id))
+            })
+            .apply_if(self.test_step_id, |select, test_step_id| {
+                select.filter(triage::Column::TestStepId.eq(test_step_id))
+            })
+            .apply_if(self.status, |select, status| {
+                select.filter(triage::Column::Status.eq(status))
+            })
+            .apply_if(self.category, |select, category| {
+                select.filter(triage::Column::Category.eq(category))
+            })
+            .apply_if(self.ticket.clone(), |select, ticket| {
+                select.filter(triage::Column::Ticket.eq(ticket))
+            })
+            .apply_if(self.author.clone(), |select, author| {
+                select.filter(triage::Column::Author.eq(author))
+            })
+            .apply_if(self.from, |select, from| {
+                select.filter(triage::Column::Date.gte(date::to_utc(from)))
+            })
+            .apply_if(self.to, |select, to| {
+                select.filter(triage::Column::Date.lte(date::to_utc(to)))
+            })
+            .order_by(triage::Column::Date, self.order.into())
+            .order_by(triage::Column::Id, self.order.into())
+    }
+
+    pub fn paginate(&self, select: Select<Triage>) -> Select<Triage> {
+        paginate(select, self.limit, self.offset)
+    }
+}
+
+fn paginate<E: EntityTrait>(
+    select: Select<E>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+) -> Select<E> {
+    select
+        .apply_if(limit, |select, limit| select.limit(limit))
+        .apply_if(offset, |select, offset| select.offset(offset))
+}
+
+/// Matches `column` case-insensitively against a name or message filter, see
+/// [`name_pattern`].
+///
+/// SQLite only folds ASCII letters, so other letters have to match exactly
+/// there.
+fn name_matches(column: impl IntoColumnRef, name: &str) -> SimpleExpr {
+    Expr::expr(Func::lower(Expr::col(column))).like(name_pattern(&name.to_lowercase()))
+}
+
+/// Translates a name or message filter into a `LIKE` pattern.
+///
+/// Names containing `*` or `?` are taken as glob patterns, all other names
+/// match as substring.
+fn name_pattern(name: &str) -> LikeExpr {
+    let mut pattern = String::with_capacity(name.len() + 2);
+    let is_glob = name.contains(['*', '?']);
+    if !is_glob {
+        pattern.push('%');
+    }
+    for c in name.chars() {
+        match c {
+            '*' => pattern.push('%'),
+            '?' => pattern.push('_'),
+            '%' | '_' | '\\' => {
+                pattern.push('\\');
+                pattern.push(c);
+            }
+            c => pattern.push(c),
+        }
+    }
+    if !is_glob {
+        pattern.push('%');
+    }
+    LikeExpr::new(pattern).escape('\\')
+}
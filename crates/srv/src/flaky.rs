@@ -0,0 +1,225 @@
+//! Detection of flaky test cases, which flip between success and failure.
+
+use entities::{prelude::*, *};
+
+use crate::error::{ErrorDetails, RgError, WithStatusCode};
+use crate::extract::{Path, Query};
+use axum::{extract::State, http::StatusCode, Json};
+use eyre::eyre;
+use sea_orm::{
+    sea_query::{
+        Alias, Expr, Order, OrderedStatement, OverStatement, Query as SqlQuery, WindowStatement,
+    },
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, FromQueryResult, QueryFilter,
+    QuerySelect, QueryTrait,
+};
+use serde::{Deserialize, Serialize};
+
+/// Number of most recent executions per test case analyzed by default
+const DEFAULT_WINDOW: usize = 20;
+
+/// Flip rate from which a test case counts as flaky by default
+const DEFAULT_MIN_FLIP_RATE: f64 = 0.2;
+
+/// Verdicts of the executions that are taken into account, all others say
+/// nothing about whether the test case passes.
+const CONCLUSIVE: [Verdict; 3] = [Verdict::Success, Verdict::Failed, Verdict::Error];
+
+#[derive(Deserialize)]
+pub(crate) struct FlakyQuery {
+    /// Number of most recent executions per test case
+    window: Option<usize>,
+    /// Test cases flipping less often are not reported
+    min_flip_rate: Option<f64>,
+    /// Analyze the test steps of the reports instead of the whole reports
+    #[serde(default)]
+    steps: bool,
+}
+
+/// Verdict of a single execution of a test case
+#[derive(FromQueryResult)]
+struct Execution {
+    name: String,
+    step: Option<String>,
+    verdict: Verdict,
+}
+
+/// A test case whose verdict keeps changing between its executions
+#[derive(Serialize)]
+pub(crate) struct FlakyTestCase {
+    /// Name of the reports
+    name: String,
+    /// Name of the test steps if they were analyzed
+    step: Option<String>,
+    /// Number of analyzed executions
+    executions: usize,
+    passed: usize,
+    failed: usize,
+    /// Number of times the outcome changed from one execution to the next
+    flips: usize,
+    /// `flips` relative to the number of possible flips
+    flip_rate: f64,
+    /// Verdict of the most recent execution
+    last_verdict: Verdict,
+}
+
+impl FlakyTestCase {
+    /// Analyzes the executions of a test case, most recent first.
+    fn analyze(name: String, step: Option<String>, verdicts: &[Verdict]) -> Option<Self> {
+        let last_verdict = *verdicts.first()?;
+        let outcomes: Vec<bool> = verdicts
+            .iter()
+            .map(|verdict| *verdict == Verdict::Success)
+            .collect();
+        let flips = flips(&outcomes);
+        let passed = outcomes.iter().filter(|passed| **passed).count();
+        Some(Self {
+            name,
+            step,
+            executions: outcomes.len(),
+            passed,
+            failed: outcomes.len() - passed,
+            flips,
+            flip_rate: flip_rate(flips, outcomes.len()),
+            last_verdict,
+        })
+    }
+}
+
+/// Number of changes between consecutive outcomes
+fn flips(outcomes: &[bool]) -> usize {
+    outcomes
+        .windows(2)
+        .filter(|pair| pair[0] != pair[1])
+        .count()
+}
+
+/// Share of the consecutive executions with different outcomes
+fn flip_rate(flips: usize, executions: usize) -> f64 {
+    if executions < 2 {
+        return 0.0;
+    }
+    flips as f64 / (executions - 1) as f64
+}
+
+/// Lists the test cases of a project whose outcome flipped between success
+/// and failure in at least `min_flip_rate` of their last `window`
+/// executions, the flakiest first.
+pub(crate) async fn get_flaky(
+    Path(project_id): Path<i32>,
+    Query(query): Query<FlakyQuery>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<FlakyTestCase>>, RgError> {
+    let project = crate::projects::find_by_id(&db, project_id).await?;
+    let window = query.window.unwrap_or(DEFAULT_WINDOW);
+    if window < 2 {
+        return Err(RgError::from(eyre!(
+            "A window of {window} executions cannot contain any flip"
+        ))
+        .with_status_code(StatusCode::BAD_REQUEST)
+        .with_details(ErrorDetails {
+            field: Some("window".to_string()),
+        }));
+    }
+    let min_flip_rate = query.min_flip_rate.unwrap_or(DEFAULT_MIN_FLIP_RATE);
+
+    // The database numbers the executions of every test case, most recent
+    // first, and only returns those within the window.
+    let mut numbering = WindowStatement::new();
+    let mut executions = if query.steps {
+        numbering
+            .partition_by((report::Entity, report::Column::Name))
+            .partition_by((test_step::Entity, test_step::Column::Name))
+            .order_by((report::Entity, report::Column::Date), Order::Desc)
+            .order_by((test_step::Entity, test_step::Column::Id), Order::Desc);
+        TestStep::find()
+            .inner_join(Report)
+            .select_only()
+            .column_as(report::Column::Name, "name")
+            .column_as(test_step::Column::Name, "step")
+            .column_as(test_step::Column::Verdict, "verdict")
+            .filter(report::Column::ProjectId.eq(project.id))
+            .filter(test_step::Column::Verdict.is_in(CONCLUSIVE))
+            .into_query()
+    } else {
+        numbering
+            .partition_by((report::Entity, report::Column::Name))
+            .order_by((report::Entity, report::Column::Date), Order::Desc)
+            .order_by((report::Entity, report::Column::Id), Order::Desc);
+        Report::find()
+            .select_only()
+            .column_as(report::Column::Name, "name")
+            // A typed NULL, PostgreSQL cannot decode an untyped one.
+            .column_as(Expr::cust("CAST(NULL AS TEXT)"), "step")
+            .column_as(report::Column::Verdict, "verdict")
+            .filter(report::Column::ProjectId.eq(project.id))
+            .filter(report::Column::Verdict.is_in(CONCLUSIVE))
+            .into_query()
+    };
+    executions.expr_window_as(
+        Expr::cust("ROW_NUMBER()"),
+        numbering,
+        Alias::new("execution"),
+    );
+    let executions = SqlQuery::select()
+        .columns([
+            Alias::new("name"),
+            Alias::new("step"),
+            Alias::new("verdict"),
+        ])
+        .from_subquery(executions, Alias::new("executions"))
+        .and_where(Expr::col(Alias::new("execution")).lte(window as i64))
+        .order_by(Alias::new("name"), Order::Asc)
+        .order_by(Alias::new("step"), Order::Asc)
+        .order_by(Alias::new("execution"), Order::Asc)
+        .to_owned();
+    let executions = Execution::find_by_statement(db.get_database_backend().build(&executions))
+        .all(&db)
+        .await?;
+
+    // Executions of the same test case are adjacent, most recent first.
+    let mut flaky = Vec::new();
+    let mut executions = executions.into_iter().peekable();
+    while let Some(Execution {
+        name,
+        step,
+        verdict,
+    }) = executions.next()
+    {
+        let mut verdicts = vec![verdict];
+        while let Some(execution) =
+            executions.next_if(|next| next.name == name && next.step == step)
+        {
+            verdicts.push(execution.verdict);
+        }
+        flaky.extend(
+            FlakyTestCase::analyze(name, step, &verdicts)
+                .filter(|case| case.flips > 0 && case.flip_rate >= min_flip_rate),
+        );
+    }
+    flaky.sort_by(|a, b| {
+        b.flip_rate
+            .total_cmp(&a.flip_rate)
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.step.cmp(&b.step))
+    });
+    Ok(Json(flaky))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flip_rate() {
+        assert_eq!(flips(&[]), 0);
+        assert_eq!(flips(&[true, true, true]), 0);
+        assert_eq!(flips(&[true, false, true, false]), 3);
+        assert_eq!(flips(&[false, false, true, true]), 1);
+
+        assert_eq!(flip_rate(0, 0), 0.0);
+        assert_eq!(flip_rate(0, 1), 0.0);
+        assert_eq!(flip_rate(3, 4), 1.0);
+        assert_eq!(flip_rate(1, 5), 0.25);
+    }
+}
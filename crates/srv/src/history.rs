@@ -0,0 +1,263 @@
+//! Verdict history of a test case, i.e. of all reports with the same name.
+
+use entities::{prelude::*, *};
+
+use crate::error::RgError;
+use crate::extract::{Path, Query};
+use axum::{extract::State, Json};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone,
+    sea_query::{Alias, Condition, Expr, Func, Order, Query as SqlQuery, SelectStatement},
+    ConnectionTrait, DatabaseConnection, DbErr, FromQueryResult,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Number of most recent results the pass rate is computed over by default
+const DEFAULT_WINDOW: u64 = 20;
+
+#[derive(Deserialize)]
+pub(crate) struct HistoryQuery {
+    /// Follow the test steps with this name instead of the whole reports
+    step: Option<String>,
+    from: Option<DateTimeWithTimeZone>,
+    to: Option<DateTimeWithTimeZone>,
+    /// Number of most recent results for `pass_rate`
+    window: Option<u64>,
+}
+
+/// A single result of the test case
+#[derive(Serialize, FromQueryResult)]
+pub(crate) struct HistoryEntry {
+    report_id: i32,
+    /// Only set for the history of a test step
+    test_step_id: Option<i32>,
+    /// Date of the report
+    date: DateTimeWithTimeZone,
+    #[serde(skip)]
+    date_offset: i32,
+    verdict: Verdict,
+}
+
+impl HistoryEntry {
+    fn with_original_offset(mut self) -> Self {
+        self.date = date::with_offset(self.date, self.date_offset);
+        self
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct History {
+    name: String,
+    step: Option<String>,
+    total: u64,
+    /// Number of results per verdict
+    verdicts: BTreeMap<Verdict, u64>,
+    /// Most recent successful result
+    last_success: Option<HistoryEntry>,
+    /// First failure since the last success, i.e. since when the test case
+    /// is failing, `None` if it did not fail since
+    first_failure: Option<HistoryEntry>,
+    window: u64,
+    /// Share of successful results among the last `window` results, `None`
+    /// without any results
+    pass_rate: Option<f64>,
+    /// All results in chronological order
+    entries: Vec<HistoryEntry>,
+}
+
+/// Returns the chronological verdicts of the reports with the given name
+/// in a project, or of their test steps with the name given in `step`,
+/// together with statistics computed by the database.
+pub(crate) async fn get_history(
+    Path((project_id, name)): Path<(i32, String)>,
+    Query(query): Query<HistoryQuery>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<History>, RgError> {
+    let project = crate::projects::find_by_id(&db, project_id).await?;
+    let base = query.results(project.id, &name);
+    let window = query.window.unwrap_or(DEFAULT_WINDOW);
+
+    let entries = fetch_entries(
+        &db,
+        base.clone()
+            .order_by((report::Entity, report::Column::Date), Order::Asc)
+            .order_by((report::Entity, report::Column::Id), Order::Asc)
+            .order_by(Alias::new("test_step_id"), Order::Asc)
+            .to_owned(),
+    )
+    .await?;
+
+    let mut verdicts = BTreeMap::new();
+    let counts = SqlQuery::select()
+        .column(Alias::new("verdict"))
+        .expr_as(Func::count(Expr::asterisk()), Alias::new("count"))
+        .from_subquery(base.clone(), Alias::new("results"))
+        .group_by_col(Alias::new("verdict"))
+        .to_owned();
+    for row in db
+        .query_all(db.get_database_backend().build(&counts))
+        .await?
+    {
+        let verdict: Verdict = row.try_get("", "verdict")?;
+        let count: i64 = row.try_get("", "count")?;
+        verdicts.insert(verdict, count as u64);
+    }
+
+    let last_success = fetch_entries(
+        &db,
+        base.clone()
+            .and_where(query.verdict_column().eq(Verdict::Success))
+            .order_by((report::Entity, report::Column::Date), Order::Desc)
+            .order_by((report::Entity, report::Column::Id), Order::Desc)
+            .limit(1)
+            .to_owned(),
+    )
+    .await?
+    .pop();
+
+    let mut failures = base.clone();
+    failures.and_where(query.verdict_column().is_in(Verdict::FAILURES));
+    if let Some(last_success) = &last_success {
+        let date = date::to_utc(last_success.date);
+        failures.cond_where(
+            Condition::any()
+                .add(Expr::col((report::Entity, report::Column::Date)).gt(date))
+                .add(
+                    Condition::all()
+                        .add(Expr::col((report::Entity, report::Column::Date)).eq(date))
+                        .add(
+                            Expr::col((report::Entity, report::Column::Id))
+                                .gt(last_success.report_id),
+                        ),
+                ),
+        );
+    }
+    let first_failure = fetch_entries(
+        &db,
+        failures
+            .order_by((report::Entity, report::Column::Date), Order::Asc)
+            .order_by((report::Entity, report::Column::Id), Order::Asc)
+            .limit(1)
+            .to_owned(),
+    )
+    .await?
+    .pop();
+
+    let recent = base
+        .clone()
+        .order_by((report::Entity, report::Column::Date), Order::Desc)
+        .order_by((report::Entity, report::Column::Id), Order::Desc)
+        .limit(window)
+        .to_owned();
+    let pass_rate = SqlQuery::select()
+        .expr_as(Func::count(Expr::asterisk()), Alias::new("total"))
+        .expr_as(
+            Func::sum(
+                Expr::case(
+                    Expr::col(Alias::new("verdict")).eq(Verdict::Success.as_str()),
+                    1,
+                )
+                .finally(0),
+            ),
+            Alias::new("passed"),
+        )
+        .from_subquery(recent, Alias::new("recent"))
+        .to_owned();
+    let pass_rate = match db
+        .query_one(db.get_database_backend().build(&pass_rate))
+        .await?
+    {
+        Some(row) => {
+            let total: i64 = row.try_get("", "total")?;
+            let passed: Option<i64> = row.try_get("", "passed")?;
+            (total > 0).then(|| passed.unwrap_or_default() as f64 / total as f64)
+        }
+        None => None,
+    };
+
+    Ok(Json(History {
+        name,
+        step: query.step,
+        total: verdicts.values().sum(),
+        verdicts,
+        last_success,
+        first_failure,
+        window,
+        pass_rate,
+        entries,
+    }))
+}
+
+impl HistoryQuery {
+    /// Query for all results with the columns of [`HistoryEntry`]
+    fn results(&self, project_id: i32, name: &str) -> SelectStatement {
+        let mut select = SqlQuery::select();
+        select
+            .expr_as(
+                Expr::col((report::Entity, report::Column::Id)),
+                Alias::new("report_id"),
+            )
+            .column((report::Entity, report::Column::Date))
+            .column((report::Entity, report::Column::DateOffset))
+            .expr_as(self.verdict_column(), Alias::new("verdict"))
+            .from(report::Entity)
+            .and_where(Expr::col((report::Entity, report::Column::ProjectId)).eq(project_id))
+            .and_where(Expr::col((report::Entity, report::Column::Name)).eq(name));
+        match &self.step {
+            Some(step) => {
+                select
+                    .expr_as(
+                        Expr::col((test_step::Entity, test_step::Column::Id)),
+                        Alias::new("test_step_id"),
+                    )
+                    .inner_join(
+                        test_step::Entity,
+                        Expr::col((test_step::Entity, test_step::Column::ReportId))
+                            .equals((report::Entity, report::Column::Id)),
+                    )
+                    .and_where(
+                        Expr::col((test_step::Entity, test_step::Column::Name)).eq(step.as_str()),
+                    );
+            }
+            None => {
+                // A typed NULL, PostgreSQL cannot decode an untyped one.
+                select.expr_as(
+                    Expr::cust("CAST(NULL AS INTEGER)"),
+                    Alias::new("test_step_id"),
+                );
+            }
+        }
+        if let Some(from) = self.from {
+            select.and_where(
+                Expr::col((report::Entity, report::Column::Date)).gte(date::to_utc(from)),
+            );
+        }
+        if let Some(to) = self.to {
+            select
+                .and_where(Expr::col((report::Entity, report::Column::Date)).lte(date::to_utc(to)));
+        }
+        select
+    }
+
+    fn verdict_column(&self) -> Expr {
+        match self.step {
+            Some(_) => Expr::col((test_step::Entity, test_step::Column::Verdict)),
+            None => Expr::col((report::Entity, report::Column::Verdict)),
+        }
+    }
+}
+
+async fn fetch_entries<C: ConnectionTrait>(
+    db: &C,
+    select: SelectStatement,
+) -> Result<Vec<HistoryEntry>, DbErr> {
+    Ok(
+        HistoryEntry::find_by_statement(db.get_database_backend().build(&select))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(HistoryEntry::with_original_offset)
+            .collect(),
+    )
+}
@@ -0,0 +1,225 @@
+//! Import of reports produced by other test tools.
+//!
+//! Every importer translates its input format into [`ImportedReport`]s which
+//! are then stored together in a single transaction.
+
+mod atx;
+mod junit;
+mod libtest;
+
+use entities::{prelude::*, *};
+
+use crate::config::Features;
+use crate::error::{RgError, WithStatusCode};
+use crate::extract::Query;
+use crate::projects;
+use crate::NewTestStep;
+use axum::{extract::State, http::StatusCode, Json};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ActiveValue, DatabaseConnection, DbErr,
+    TransactionError, TransactionTrait,
+};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ImportedReport {
+    pub name: String,
+    pub date: DateTimeWithTimeZone,
+    pub duration: Option<chrono::Duration>,
+    /// Output of the whole report, e.g. of the setup of a test suite
+    pub output: Option<String>,
+    /// Verdict given by the test tool, derived from the steps if `None`
+    pub verdict: Option<Verdict>,
+    pub steps: Vec<ImportedStep>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ImportedStep {
+    pub name: String,
+    pub date: DateTimeWithTimeZone,
+    pub verdict: Verdict,
+    pub duration: Option<chrono::Duration>,
+    pub output: Option<String>,
+    /// Why the step got its verdict, e.g. the message of a failed assertion
+    pub message: Option<String>,
+    pub failure_category: Option<FailureCategory>,
+    pub children: Vec<ImportedStep>,
+}
+
+impl ImportedStep {
+    /// Worst verdict of the step and all its sub-steps
+    fn worst_verdict(&self) -> Verdict {
+        Verdict::worst(
+            std::iter::once(self.verdict).chain(self.children.iter().map(Self::worst_verdict)),
+        )
+    }
+
+    /// Converts the step and its sub-steps, which are numbered in order.
+    fn into_new_test_step(self, step_number: i32) -> NewTestStep {
+        NewTestStep {
+            name: self.name,
+            step_number,
+            date: self.date,
+            verdict: self.verdict,
+            duration_ms: self.duration.map(|duration| duration.num_milliseconds()),
+            output: self.output,
+            message: self.message,
+            expected: None,
+            actual: None,
+            unit: None,
+            failure_category: self.failure_category,
+            parent_id: None,
+            children: number_steps(self.children),
+            measurements: Vec::new(),
+        }
+    }
+}
+
+fn number_steps(steps: Vec<ImportedStep>) -> Vec<NewTestStep> {
+    steps
+        .into_iter()
+        .zip(1..)
+        .map(|(step, step_number)| step.into_new_test_step(step_number))
+        .collect()
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ImportParams {
+    project: String,
+    /// Report name for formats that do not contain one
+    name: Option<String>,
+}
+
+pub(crate) async fn import_junit(
+    Query(params): Query<ImportParams>,
+    State(db): State<DatabaseConnection>,
+    State(features): State<Features>,
+    document: String,
+) -> Result<Json<Vec<i32>>, RgError> {
+    let reports = junit::parse(&document).with_status_code(StatusCode::BAD_REQUEST)?;
+    let project = projects::find_by_name(&db, features, &params.project).await?;
+    Ok(Json(store(&db, project, reports).await?))
+}
+
+pub(crate) async fn import_atx(
+    Query(params): Query<ImportParams>,
+    State(db): State<DatabaseConnection>,
+    State(features): State<Features>,
+    document: String,
+) -> Result<Json<Vec<i32>>, RgError> {
+    let reports = atx::parse(&document).with_status_code(StatusCode::BAD_REQUEST)?;
+    let project = projects::find_by_name(&db, features, &params.project).await?;
+    Ok(Json(store(&db, project, reports).await?))
+}
+
+pub(crate) async fn import_libtest(
+    Query(params): Query<ImportParams>,
+    State(db): State<DatabaseConnection>,
+    State(features): State<Features>,
+    document: String,
+) -> Result<Json<Vec<i32>>, RgError> {
+    let name = params.name.as_deref().unwrap_or("cargo test");
+    let reports = libtest::parse(&document, name, chrono::Utc::now().into())
+        .with_status_code(StatusCode::BAD_REQUEST)?;
+    let project = projects::find_by_name(&db, features, &params.project).await?;
+    Ok(Json(store(&db, project, reports).await?))
+}
+
+/// Parses an RFC 3339 timestamp, timestamps without an offset are taken as
+/// UTC.
+fn parse_timestamp(timestamp: &str) -> Option<DateTimeWithTimeZone> {
+    let timestamp = timestamp.trim();
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S%.f")
+                .ok()
+                .map(|date| chrono::DateTime::<chrono::Utc>::from_utc(date, chrono::Utc).into())
+        })
+}
+
+/// Parses a duration given in (fractional) seconds.
+///
+/// Commas are thousands separators if there is also a decimal point, e.g.
+/// `1,234.5`, otherwise a single comma is the decimal separator, e.g. `1,5`.
+fn parse_duration(seconds: &str) -> Option<chrono::Duration> {
+    let seconds = seconds.trim();
+    let seconds = if seconds.contains('.') {
+        seconds.replace(',', "")
+    } else {
+        seconds.replacen(',', ".", 1)
+    };
+    duration_from_secs(seconds.parse().ok()?)
+}
+
+/// Longest duration that is accepted from imported reports, anything longer
+/// is certainly a broken value
+const MAX_DURATION_SECS: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+fn duration_from_secs(seconds: f64) -> Option<chrono::Duration> {
+    (seconds.is_finite() && (0.0..=MAX_DURATION_SECS).contains(&seconds))
+        .then(|| chrono::Duration::milliseconds((seconds * 1000.0).round() as i64))
+}
+
+/// Stores the imported reports with all their steps and returns the ids of the
+/// created reports.
+///
+/// The verdict of imported reports without a verdict of their own is derived
+/// from their steps and sub-steps.
+async fn store(
+    db: &DatabaseConnection,
+    project: project::Model,
+    reports: Vec<ImportedReport>,
+) -> Result<Vec<i32>, TransactionError<DbErr>> {
+    db.transaction(|txn| {
+        Box::pin(async move {
+            let mut report_ids = Vec::with_capacity(reports.len());
+            for report in reports {
+                let computed_verdict = (!report.steps.is_empty())
+                    .then(|| Verdict::worst(report.steps.iter().map(ImportedStep::worst_verdict)));
+                let derive_verdict = report.verdict.is_none();
+                let verdict = report.verdict.or(computed_verdict).unwrap_or_default();
+                let report_model = report::ActiveModel {
+                    date: ActiveValue::Set(date::to_utc(report.date)),
+                    date_offset: ActiveValue::Set(date::offset_seconds(&report.date)),
+                    duration_ms: ActiveValue::Set(
+                        report.duration.map(|duration| duration.num_milliseconds()),
+                    ),
+                    output: ActiveValue::Set(report.output),
+                    project: ActiveValue::Set(project.name.clone()),
+                    project_id: ActiveValue::Set(Some(project.id)),
+                    name: ActiveValue::Set(report.name),
+                    verdict: ActiveValue::Set(verdict),
+                    declared_verdict: ActiveValue::Set(verdict),
+                    computed_verdict: ActiveValue::Set(computed_verdict),
+                    derive_verdict: ActiveValue::Set(Some(derive_verdict)),
+                    ..Default::default()
+                };
+                let created = report_model.insert(txn).await?;
+                let report_id = created.id;
+
+                crate::insert_test_steps(txn, report_id, None, number_steps(report.steps)).await?;
+                crate::baselines::compare(txn, created).await?;
+                report_ids.push(report_id);
+            }
+            Ok(report_ids)
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        let ms = |ms| Some(chrono::Duration::milliseconds(ms));
+        assert_eq!(parse_duration(" 2.25 "), ms(2250));
+        assert_eq!(parse_duration("1,234.5"), ms(1_234_500));
+        assert_eq!(parse_duration("1,5"), ms(1500));
+        assert_eq!(parse_duration("1,234,567"), None);
+        assert_eq!(parse_duration("-1"), None);
+        assert_eq!(parse_duration("1e14"), None);
+    }
+}
@@ -0,0 +1,224 @@
+//! Importer for ASAM ATX reports as exported by ecu.test.
+//!
+//! Every `<TEST-CASE>` with a verdict becomes a report. Its steps are collected
+//! from all `<TEST-STEP>` and `<TEST-STEP-FOLDER>` elements below it in document
+//! order, folders are imported as steps of their own with the elements inside
+//! them as sub-steps.
+//!
+//! Names are taken from `<LONG-NAME>` or `<SHORT-NAME>`, verdicts from a
+//! `<VERDICT>` element either directly below the element or wrapped in a
+//! `<VERDICT-RESULT>`. Timestamps are read from a `<DATE>` element or from a
+//! special data `<SD GID="Timestamp">` and may be given either as RFC 3339 or
+//! as seconds since the epoch. Steps without a timestamp inherit the one of
+//! their predecessor. Durations are read from `<SD GID="Duration">` in seconds.
+
+use super::{parse_duration, parse_timestamp, ImportedReport, ImportedStep};
+use chrono::TimeZone;
+use entities::prelude::*;
+use eyre::eyre;
+use roxmltree::{Document, Node};
+use sea_orm::prelude::DateTimeWithTimeZone;
+
+pub(super) fn parse(document: &str) -> eyre::Result<Vec<ImportedReport>> {
+    let document = Document::parse(document)?;
+    let reports: Vec<_> = document
+        .descendants()
+        .filter(|node| node.has_tag_name("TEST-CASE"))
+        .filter_map(|case| verdict(case).map(|verdict| parse_case(case, verdict)))
+        .collect();
+    if reports.is_empty() {
+        return Err(eyre!(
+            "Document does not contain any <TEST-CASE> with a verdict"
+        ));
+    }
+    Ok(reports)
+}
+
+fn parse_case(case: Node, verdict: Verdict) -> ImportedReport {
+    let date = timestamp(case).unwrap_or_else(|| chrono::Utc::now().into());
+
+    let mut step_date = date;
+    ImportedReport {
+        name: name(case),
+        date,
+        duration: duration(case),
+        output: None,
+        verdict: Some(verdict),
+        steps: parse_steps(case, &mut step_date),
+    }
+}
+
+/// Collects the outermost steps and folders below `node` in document order
+/// together with their own sub-steps.
+fn parse_steps(node: Node, step_date: &mut DateTimeWithTimeZone) -> Vec<ImportedStep> {
+    let mut steps = Vec::new();
+    for child in node.children().filter(Node::is_element) {
+        if child.has_tag_name("TEST-STEP") || child.has_tag_name("TEST-STEP-FOLDER") {
+            *step_date = timestamp(child).unwrap_or(*step_date);
+            steps.push(ImportedStep {
+                name: name(child),
+                date: *step_date,
+                verdict: verdict(child).unwrap_or_default(),
+                duration: duration(child),
+                output: None,
+                message: None,
+                failure_category: None,
+                children: parse_steps(child, step_date),
+            });
+        } else {
+            steps.extend(parse_steps(child, step_date));
+        }
+    }
+    steps
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag_name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(tag_name))
+}
+
+fn text<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    node.text().map(str::trim).filter(|text| !text.is_empty())
+}
+
+fn name(node: Node) -> String {
+    child(node, "LONG-NAME")
+        .and_then(|long_name| child(long_name, "L-4").or(Some(long_name)))
+        .and_then(text)
+        .or_else(|| child(node, "SHORT-NAME").and_then(text))
+        .unwrap_or(node.tag_name().name())
+        .to_string()
+}
+
+fn verdict(node: Node) -> Option<Verdict> {
+    let verdict = child(node, "VERDICT")
+        .or_else(|| child(node, "VERDICT-RESULT").and_then(|result| child(result, "VERDICT")))?;
+    Some(match text(verdict)?.to_uppercase().as_str() {
+        "PASSED" | "SUCCESS" => Verdict::Success,
+        "INCONCLUSIVE" => Verdict::Inconclusive,
+        "FAILED" => Verdict::Failed,
+        "ERROR" => Verdict::Error,
+        _ => Verdict::None,
+    })
+}
+
+/// Text of the special data `<SD>` with the given `GID`
+fn special_data<'a>(node: Node<'a, '_>, gid: &str) -> Option<&'a str> {
+    child(node, "SDGS")
+        .into_iter()
+        .flat_map(|sdgs| sdgs.descendants())
+        .find(|sd| sd.has_tag_name("SD") && sd.attribute("GID") == Some(gid))
+        .and_then(text)
+}
+
+fn duration(node: Node) -> Option<chrono::Duration> {
+    special_data(node, "Duration").and_then(parse_duration)
+}
+
+fn timestamp(node: Node) -> Option<DateTimeWithTimeZone> {
+    let timestamp = child(node, "DATE")
+        .and_then(text)
+        .or_else(|| special_data(node, "Timestamp"))?;
+    parse_timestamp(timestamp).or_else(|| {
+        let seconds: f64 = timestamp.parse().ok()?;
+        chrono::Utc
+            .timestamp_millis_opt((seconds * 1000.0).round() as i64)
+            .single()
+            .map(Into::into)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let reports = parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <ATX xmlns="http://www.asam.net/xml">
+              <AR-PACKAGES><AR-PACKAGE>
+                <SHORT-NAME>TestExecution</SHORT-NAME>
+                <ELEMENTS>
+                  <TEST-CASE>
+                    <SHORT-NAME>MyFancyTestCase</SHORT-NAME>
+                    <LONG-NAME><L-4 L="FOR-ALL">MyFancyTestCase.pkg</L-4></LONG-NAME>
+                    <DATE>2014-11-28T21:00:09+09:00</DATE>
+                    <VERDICT-RESULT><VERDICT>FAILED</VERDICT></VERDICT-RESULT>
+                    <TEST-EXECUTION-STEPS>
+                      <TEST-STEP-FOLDER>
+                        <SHORT-NAME>Precondition</SHORT-NAME>
+                        <VERDICT-RESULT><VERDICT>PASSED</VERDICT></VERDICT-RESULT>
+                        <TEST-STEPS>
+                          <TEST-STEP>
+                            <SHORT-NAME>Bus_Lesen</SHORT-NAME>
+                            <LONG-NAME><L-4>Bus Lesen</L-4></LONG-NAME>
+                            <SDGS><SDG GID="ecu.test"><SD GID="Timestamp">1417176010.5</SD><SD GID="Duration">0.75</SD></SDG></SDGS>
+                            <VERDICT-RESULT><VERDICT>PASSED</VERDICT></VERDICT-RESULT>
+                          </TEST-STEP>
+                        </TEST-STEPS>
+                      </TEST-STEP-FOLDER>
+                      <TEST-STEP>
+                        <SHORT-NAME>Mess_Lesen</SHORT-NAME>
+                        <VERDICT>FAILED</VERDICT>
+                      </TEST-STEP>
+                    </TEST-EXECUTION-STEPS>
+                  </TEST-CASE>
+                  <TEST-CASE>
+                    <SHORT-NAME>NotExecuted</SHORT-NAME>
+                  </TEST-CASE>
+                </ELEMENTS>
+              </AR-PACKAGE></AR-PACKAGES>
+            </ATX>"#,
+        )
+        .unwrap();
+
+        let date = |s| chrono::DateTime::parse_from_rfc3339(s).unwrap();
+        assert_eq!(
+            reports,
+            [ImportedReport {
+                name: "MyFancyTestCase.pkg".to_string(),
+                date: date("2014-11-28T21:00:09+09:00"),
+                duration: None,
+                output: None,
+                verdict: Some(Verdict::Failed),
+                steps: vec![
+                    ImportedStep {
+                        name: "Precondition".to_string(),
+                        date: date("2014-11-28T21:00:09+09:00"),
+                        verdict: Verdict::Success,
+                        duration: None,
+                        output: None,
+                        message: None,
+                        failure_category: None,
+                        children: vec![ImportedStep {
+                            name: "Bus Lesen".to_string(),
+                            date: date("2014-11-28T12:00:10.5+00:00"),
+                            verdict: Verdict::Success,
+                            duration: Some(chrono::Duration::milliseconds(750)),
+                            output: None,
+                            message: None,
+                            failure_category: None,
+                            children: vec![],
+                        }],
+                    },
+                    ImportedStep {
+                        name: "Mess_Lesen".to_string(),
+                        date: date("2014-11-28T12:00:10.5+00:00"),
+                        verdict: Verdict::Failed,
+                        duration: None,
+                        output: None,
+                        message: None,
+                        failure_category: None,
+                        children: vec![],
+                    },
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("<ATX>").is_err());
+        assert!(parse("<ATX><TEST-CASE/></ATX>").is_err());
+    }
+}
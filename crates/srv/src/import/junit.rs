@@ -0,0 +1,241 @@
+//! Importer for JUnit XML reports.
+//!
+//! Every `<testsuite>` containing test cases becomes a report and every
+//! `<testcase>` one of its steps.
+
+use super::{parse_duration, parse_timestamp, ImportedReport, ImportedStep};
+use entities::prelude::*;
+use eyre::eyre;
+use roxmltree::{Document, Node};
+use sea_orm::prelude::DateTimeWithTimeZone;
+
+pub(super) fn parse(document: &str) -> eyre::Result<Vec<ImportedReport>> {
+    let document = Document::parse(document)?;
+    let reports: Vec<_> = document
+        .descendants()
+        .filter(|node| node.has_tag_name("testsuite"))
+        .filter(|suite| suite.children().any(|node| node.has_tag_name("testcase")))
+        .map(parse_suite)
+        .collect::<eyre::Result<_>>()?;
+    if reports.is_empty() {
+        return Err(eyre!(
+            "Document does not contain any <testsuite> with test cases"
+        ));
+    }
+    Ok(reports)
+}
+
+fn parse_suite(suite: Node) -> eyre::Result<ImportedReport> {
+    let date = suite
+        .attribute("timestamp")
+        .and_then(parse_timestamp)
+        .unwrap_or_else(|| chrono::Utc::now().into());
+
+    let mut step_date = date;
+    let steps = suite
+        .children()
+        .filter(|node| node.has_tag_name("testcase"))
+        .map(|case| {
+            let step = parse_case(case, step_date);
+            step_date = end_date(&step)?;
+            Ok(step)
+        })
+        .collect::<eyre::Result<_>>()?;
+
+    Ok(ImportedReport {
+        name: suite.attribute("name").unwrap_or("testsuite").to_string(),
+        date,
+        duration: suite.attribute("time").and_then(parse_duration),
+        output: output(suite),
+        verdict: None,
+        steps,
+    })
+}
+
+/// When a test case ended, which is when the next one started.
+fn end_date(step: &ImportedStep) -> eyre::Result<DateTimeWithTimeZone> {
+    match step.duration {
+        Some(duration) => step.date.checked_add_signed(duration).ok_or_else(|| {
+            eyre!(
+                "Test case {} ends after the latest supported date",
+                step.name
+            )
+        }),
+        None => Ok(step.date),
+    }
+}
+
+fn parse_case(case: Node, date: DateTimeWithTimeZone) -> ImportedStep {
+    let name = case.attribute("name").unwrap_or("testcase");
+    let name = match case.attribute("classname") {
+        Some(class_name) if !class_name.is_empty() => format!("{class_name}.{name}"),
+        _ => name.to_string(),
+    };
+
+    let result = case
+        .children()
+        .filter(|node| node.is_element())
+        .find_map(|node| match node.tag_name().name() {
+            "error" => Some((node, Verdict::Error, Some(FailureCategory::Exception))),
+            "failure" => Some((node, Verdict::Failed, Some(FailureCategory::Assertion))),
+            "skipped" => Some((node, Verdict::Skipped, None)),
+            _ => None,
+        });
+    let verdict = result.map_or(Verdict::Success, |(_, verdict, _)| verdict);
+    // Prefer the short message attribute, the element text is often a
+    // complete stack trace.
+    let message = result.and_then(|(node, _, _)| {
+        node.attribute("message")
+            .map(str::trim)
+            .filter(|message| !message.is_empty())
+            .or_else(|| node.text().map(str::trim).filter(|text| !text.is_empty()))
+            .map(str::to_string)
+    });
+
+    ImportedStep {
+        name,
+        date,
+        verdict,
+        duration: case.attribute("time").and_then(parse_duration),
+        output: output(case),
+        message,
+        failure_category: result.and_then(|(_, _, failure_category)| failure_category),
+        children: vec![],
+    }
+}
+
+/// Joins the `<system-out>` and `<system-err>` of a test suite or case.
+fn output(node: Node) -> Option<String> {
+    let output: Vec<_> = node
+        .children()
+        .filter(|node| node.has_tag_name("system-out") || node.has_tag_name("system-err"))
+        .filter_map(|node| node.text())
+        .filter(|text| !text.trim().is_empty())
+        .collect();
+    (!output.is_empty()).then(|| output.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let reports = parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <testsuites>
+                <testsuite name="first" timestamp="2014-11-28T21:00:09+09:00" tests="4" time="2.25">
+                    <testcase classname="bus" name="Bus Lesen" time="1.5">
+                        <system-out>0x42</system-out>
+                    </testcase>
+                    <testcase classname="bus" name="Mess Lesen" time="0.5">
+                        <failure message="expected 1, got 2">assertion failed</failure>
+                    </testcase>
+                    <testcase name="Kalib Lesen">
+                        <error message="timeout"/>
+                    </testcase>
+                    <testcase name="Flash">
+                        <skipped>not flashed</skipped>
+                    </testcase>
+                    <system-out>suite output</system-out>
+                    <system-err>suite warning</system-err>
+                </testsuite>
+                <testsuite name="second" timestamp="2015-11-28T12:00:09">
+                    <testcase name="Mess Lesen" time="2"/>
+                </testsuite>
+                <testsuite name="empty"/>
+            </testsuites>"#,
+        )
+        .unwrap();
+
+        let date = |s| chrono::DateTime::parse_from_rfc3339(s).unwrap();
+        assert_eq!(
+            reports,
+            [
+                ImportedReport {
+                    name: "first".to_string(),
+                    date: date("2014-11-28T21:00:09+09:00"),
+                    duration: Some(chrono::Duration::milliseconds(2250)),
+                    output: Some("suite output\nsuite warning".to_string()),
+                    verdict: None,
+                    steps: vec![
+                        ImportedStep {
+                            name: "bus.Bus Lesen".to_string(),
+                            date: date("2014-11-28T21:00:09+09:00"),
+                            verdict: Verdict::Success,
+                            duration: Some(chrono::Duration::milliseconds(1500)),
+                            output: Some("0x42".to_string()),
+                            message: None,
+                            failure_category: None,
+                            children: vec![],
+                        },
+                        ImportedStep {
+                            name: "bus.Mess Lesen".to_string(),
+                            date: date("2014-11-28T21:00:10.5+09:00"),
+                            verdict: Verdict::Failed,
+                            duration: Some(chrono::Duration::milliseconds(500)),
+                            output: None,
+                            message: Some("expected 1, got 2".to_string()),
+                            failure_category: Some(FailureCategory::Assertion),
+                            children: vec![],
+                        },
+                        ImportedStep {
+                            name: "Kalib Lesen".to_string(),
+                            date: date("2014-11-28T21:00:11+09:00"),
+                            verdict: Verdict::Error,
+                            duration: None,
+                            output: None,
+                            message: Some("timeout".to_string()),
+                            failure_category: Some(FailureCategory::Exception),
+                            children: vec![],
+                        },
+                        ImportedStep {
+                            name: "Flash".to_string(),
+                            date: date("2014-11-28T21:00:11+09:00"),
+                            verdict: Verdict::Skipped,
+                            duration: None,
+                            output: None,
+                            message: Some("not flashed".to_string()),
+                            failure_category: None,
+                            children: vec![],
+                        },
+                    ],
+                },
+                ImportedReport {
+                    name: "second".to_string(),
+                    date: date("2015-11-28T12:00:09+00:00"),
+                    duration: None,
+                    output: None,
+                    verdict: None,
+                    steps: vec![ImportedStep {
+                        name: "Mess Lesen".to_string(),
+                        date: date("2015-11-28T12:00:09+00:00"),
+                        verdict: Verdict::Success,
+                        duration: Some(chrono::Duration::seconds(2)),
+                        output: None,
+                        message: None,
+                        failure_category: None,
+                        children: vec![],
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("<testsuite>").is_err());
+        assert!(parse("<testsuites><testsuite/></testsuites>").is_err());
+
+        // Durations beyond the limit are ignored, those within it must not
+        // move the following steps beyond the last representable date.
+        let mut reports =
+            parse(r#"<testsuite><testcase name="a" time="1e14"/></testsuite>"#).unwrap();
+        let step = &mut reports[0].steps[0];
+        assert_eq!(step.duration, None);
+        step.date = chrono::DateTime::<chrono::Utc>::MAX_UTC.into();
+        step.duration = Some(chrono::Duration::seconds(1));
+        let error = end_date(step).unwrap_err();
+        assert!(error.to_string().contains("Test case a "), "{error}");
+    }
+}
@@ -0,0 +1,285 @@
+//! Importer for the JSON output of libtest, e.g. produced by
+//! `cargo test -- -Z unstable-options --format json` or nextest's
+//! libtest-json output.
+//!
+//! Every test suite (i.e. test binary) becomes a report and every finished
+//! test one of its steps. The output does not contain any timestamps and tests
+//! run in parallel, so reports and steps are dated at the time of the import.
+
+use super::{duration_from_secs, ImportedReport, ImportedStep};
+use entities::prelude::*;
+use eyre::eyre;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Event {
+    Suite(SuiteEvent),
+    Test(TestEvent),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct SuiteEvent {
+    event: String,
+    exec_time: Option<f64>,
+    nextest: Option<NextestSuite>,
+}
+
+#[derive(Deserialize)]
+struct NextestSuite {
+    #[serde(rename = "crate")]
+    crate_name: String,
+    test_binary: String,
+}
+
+#[derive(Deserialize)]
+struct TestEvent {
+    event: String,
+    name: String,
+    exec_time: Option<f64>,
+    stdout: Option<String>,
+    /// Reason of a failure or of an ignored test
+    message: Option<String>,
+}
+
+/// Parses the libtest output, `name` is used for suites whose name is unknown.
+pub(super) fn parse(
+    document: &str,
+    name: &str,
+    date: DateTimeWithTimeZone,
+) -> eyre::Result<Vec<ImportedReport>> {
+    let mut reports = Vec::new();
+    let mut unnamed_reports = Vec::new();
+    let mut current: Option<ImportedReport> = None;
+
+    // Skip everything that is not a JSON object, e.g. lines written by cargo,
+    // but count it for the line numbers in errors.
+    let lines = document
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| line.starts_with('{'));
+    for (line_number, line) in lines {
+        let event = serde_json::from_str(line)
+            .map_err(|e| eyre!("Invalid event in line {}: {e}", line_number + 1))?;
+        match (event, current.as_mut()) {
+            (Event::Suite(suite), _) if suite.event == "started" => {
+                // Suites of crashed test binaries never finish
+                reports.extend(current.take().map(unfinished));
+                if let Some(suite) = suite.nextest {
+                    current = Some(ImportedReport {
+                        name: format!("{}::{}", suite.crate_name, suite.test_binary),
+                        date,
+                        duration: None,
+                        output: None,
+                        verdict: None,
+                        steps: Vec::new(),
+                    });
+                } else {
+                    unnamed_reports.push(reports.len());
+                    current = Some(ImportedReport {
+                        name: name.to_string(),
+                        date,
+                        duration: None,
+                        output: None,
+                        verdict: None,
+                        steps: Vec::new(),
+                    });
+                }
+            }
+            (Event::Suite(suite), Some(report)) => {
+                report.duration = suite.exec_time.and_then(duration_from_secs);
+                reports.extend(current.take());
+            }
+            (Event::Test(test), Some(report)) => {
+                let verdict = match test.event.as_str() {
+                    "ok" => Verdict::Success,
+                    "failed" => Verdict::Failed,
+                    "ignored" => Verdict::Skipped,
+                    // "started" and "timeout" are emitted while the test is still running
+                    _ => continue,
+                };
+                report.steps.push(ImportedStep {
+                    name: test.name,
+                    date,
+                    verdict,
+                    duration: test.exec_time.and_then(duration_from_secs),
+                    output: test.stdout.filter(|stdout| !stdout.is_empty()),
+                    message: test.message,
+                    failure_category: None,
+                    children: vec![],
+                });
+            }
+            _ => (),
+        }
+    }
+    // Output of an aborted test run
+    reports.extend(current.map(unfinished));
+
+    if reports.is_empty() {
+        return Err(eyre!("Document does not contain any test suite"));
+    }
+    if unnamed_reports.len() > 1 {
+        for (number, index) in unnamed_reports.into_iter().enumerate() {
+            reports[index].name = format!("{} #{}", reports[index].name, number + 1);
+        }
+    }
+    Ok(reports)
+}
+
+/// Marks a suite that was started but never finished as error, the steps
+/// cannot tell since the tests that were running are missing.
+fn unfinished(mut report: ImportedReport) -> ImportedReport {
+    report.verdict = Some(Verdict::Error);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let date = chrono::DateTime::parse_from_rfc3339("2023-05-01T12:00:00+02:00").unwrap();
+        let reports = parse(
+            r#"
+            { "type": "suite", "event": "started", "test_count": 3 }
+            { "type": "test", "event": "started", "name": "tests::a" }
+            { "type": "test", "event": "started", "name": "tests::b" }
+            { "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.25 }
+            { "type": "test", "name": "tests::b", "event": "failed", "exec_time": 1.0, "stdout": "thread 'tests::b' panicked\n" }
+            { "type": "test", "event": "ignored", "name": "tests::c", "message": "needs a bench" }
+            { "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "exec_time": 1.1 }
+                 Running unittests src/main.rs
+            { "type": "suite", "event": "started", "test_count": 0 }
+            { "type": "suite", "event": "ok", "passed": 0, "failed": 0, "ignored": 0 }
+            { "type": "suite", "event": "started", "test_count": 1, "nextest": { "crate": "srv", "test_binary": "result_guide", "kind": "bin" } }
+            { "type": "bench", "name": "bench", "median": 1, "deviation": 0 }
+            { "type": "test", "event": "ok", "name": "tests::d" }
+            "#,
+            "cargo test",
+            date,
+        )
+        .unwrap();
+
+        assert_eq!(
+            reports,
+            [
+                ImportedReport {
+                    name: "cargo test #1".to_string(),
+                    date,
+                    duration: Some(chrono::Duration::milliseconds(1100)),
+                    output: None,
+                    verdict: None,
+                    steps: vec![
+                        ImportedStep {
+                            name: "tests::a".to_string(),
+                            date,
+                            verdict: Verdict::Success,
+                            duration: Some(chrono::Duration::milliseconds(250)),
+                            output: None,
+                            message: None,
+                            failure_category: None,
+                            children: vec![],
+                        },
+                        ImportedStep {
+                            name: "tests::b".to_string(),
+                            date,
+                            verdict: Verdict::Failed,
+                            duration: Some(chrono::Duration::seconds(1)),
+                            output: Some("thread 'tests::b' panicked\n".to_string()),
+                            message: None,
+                            failure_category: None,
+                            children: vec![],
+                        },
+                        ImportedStep {
+                            name: "tests::c".to_string(),
+                            date,
+                            verdict: Verdict::Skipped,
+                            duration: None,
+                            output: None,
+                            message: Some("needs a bench".to_string()),
+                            failure_category: None,
+                            children: vec![],
+                        },
+                    ],
+                },
+                ImportedReport {
+                    name: "cargo test #2".to_string(),
+                    date,
+                    duration: None,
+                    output: None,
+                    verdict: None,
+                    steps: vec![],
+                },
+                ImportedReport {
+                    name: "srv::result_guide".to_string(),
+                    date,
+                    duration: None,
+                    output: None,
+                    verdict: Some(Verdict::Error),
+                    steps: vec![ImportedStep {
+                        name: "tests::d".to_string(),
+                        date,
+                        verdict: Verdict::Success,
+                        duration: None,
+                        output: None,
+                        message: None,
+                        failure_category: None,
+                        children: vec![],
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_unfinished_suite() {
+        let date = chrono::Utc::now().into();
+        let reports = parse(
+            r#"
+            { "type": "suite", "event": "started", "test_count": 2 }
+            { "type": "test", "name": "tests::a", "event": "ok" }
+            { "type": "test", "event": "started", "name": "tests::b" }
+            { "type": "suite", "event": "started", "test_count": 1 }
+            { "type": "test", "name": "tests::c", "event": "ok" }
+            { "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0 }
+            "#,
+            "cargo test",
+            date,
+        )
+        .unwrap();
+
+        let summary: Vec<_> = reports
+            .iter()
+            .map(|report| (report.name.as_str(), report.verdict, report.steps.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("cargo test #1", Some(Verdict::Error), 1),
+                ("cargo test #2", None, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let date = chrono::Utc::now().into();
+        assert!(parse("running 1 test", "cargo test", date).is_err());
+        assert!(parse(r#"{ "type": "suite" }"#, "cargo test", date).is_err());
+        let error = parse(
+            "   Compiling result_guide v0.1.0\n\n{ \"type\": \"suite\" }",
+            "cargo test",
+            date,
+        )
+        .unwrap_err();
+        assert!(
+            error.to_string().starts_with("Invalid event in line 3:"),
+            "{error}"
+        );
+    }
+}
@@ -1,55 +1,202 @@
+mod attachments;
+mod baselines;
+mod blob_store;
+mod config;
 mod error;
+mod extract;
+mod filter;
+mod flaky;
+mod history;
+mod import;
+mod measurements;
 mod migrator;
+mod projects;
+mod report_metadata;
+mod test_runs;
+mod triage;
 
 use entities::{prelude::*, *};
 
-use crate::error::{RgError, WithStatusCode};
+use crate::blob_store::{BlobStore, LocalBlobStore};
+use crate::config::{Cli, Config, Features};
+use crate::error::{ErrorDetails, RgError, WithStatusCode};
+use crate::extract::{Path, Query};
+use crate::filter::{ReportFilter, TestStepFilter};
+use crate::measurements::NewMeasurement;
 use axum::{
-    extract::{Path, State},
+    extract::{DefaultBodyLimit, FromRef, RawQuery, State},
     http::StatusCode,
-    routing::{get, post},
+    middleware,
+    routing::{get, post, put},
     Json, Router,
 };
+use clap::Parser;
 use eyre::eyre;
 use sea_orm::{
-    ActiveValue, ColumnTrait, Database, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter,
-    TransactionTrait,
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait,
+    Database, DatabaseConnection, EntityTrait, ModelTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, TransactionTrait,
 };
 use sea_orm_migration::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::sync::Arc;
+use tracing_subscriber::{filter::Targets, prelude::*};
 
-const DB_URL: &str = "sqlite:./sqlite.db?mode=rwc";
+fn init_tracing(log_level: &str) -> eyre::Result<()> {
+    let targets: Targets = log_level
+        .parse()
+        .map_err(|e| eyre!("Invalid log level {log_level}: {e}"))?;
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_test_writer())
+        .with(targets)
+        .init();
+    Ok(())
+}
 
-fn init_tracing() {
-    tracing_subscriber::fmt().with_test_writer().init();
+#[derive(Clone, FromRef)]
+struct AppState {
+    db: DatabaseConnection,
+    features: Features,
+    blobs: Arc<dyn BlobStore>,
 }
 
-fn app(db: DatabaseConnection) -> Router {
-    Router::new()
+fn app(
+    db: DatabaseConnection,
+    features: Features,
+    blobs: Arc<dyn BlobStore>,
+    max_attachment_size: usize,
+) -> Router {
+    let router = Router::new()
         .route("/reports", post(add_report).get(get_reports))
-        .route("/reports/:id", get(get_single_report).delete(delete_report))
+        .route("/reports/bulk", post(add_report_with_steps))
+        .route(
+            "/reports/:id",
+            get(get_single_report)
+                .put(replace_report)
+                .patch(update_report)
+                .delete(delete_report),
+        )
         .route(
             "/reports/:id/test_steps",
             post(add_teststep).get(get_teststeps_for_report),
         )
+        .route("/reports/:id/test_steps/tree", get(get_teststep_tree))
+        .route("/reports/:id/diff/:other", get(get_report_diff))
+        .route(
+            "/reports/:id/triage",
+            post(triage::add_triage).get(triage::get_triage_for_report),
+        )
+        .route(
+            "/reports/:id/attachments",
+            post(attachments::add_attachments)
+                .layer(DefaultBodyLimit::max(max_attachment_size))
+                .get(attachments::get_attachments_for_report),
+        )
         .route("/test_steps", get(get_teststeps))
-        .route("/test_steps/:id", get(get_single_teststep))
-        .with_state(db)
+        .route(
+            "/test_steps/:id",
+            get(get_single_teststep)
+                .patch(update_teststep)
+                .delete(delete_teststep),
+        )
+        .route(
+            "/test_steps/:id/attachments",
+            get(attachments::get_attachments_for_teststep),
+        )
+        .route(
+            "/test_steps/:id/measurements",
+            post(measurements::add_measurements).get(measurements::get_measurements_for_teststep),
+        )
+        .route("/measurements", get(measurements::get_measurements))
+        .route("/triage", get(triage::get_triage))
+        .route(
+            "/triage/:id",
+            get(triage::get_single_triage).delete(triage::delete_triage),
+        )
+        .route("/attachments/:id", get(attachments::get_single_attachment))
+        .route(
+            "/attachments/:id/content",
+            get(attachments::download_attachment),
+        )
+        .route(
+            "/projects",
+            post(projects::add_project).get(projects::get_projects),
+        )
+        .route(
+            "/projects/:id",
+            get(projects::get_single_project)
+                .patch(projects::update_project)
+                .delete(projects::delete_project),
+        )
+        .route(
+            "/projects/:id/reports",
+            get(projects::get_reports_for_project),
+        )
+        .route(
+            "/projects/:id/baseline",
+            put(baselines::set_baseline).delete(baselines::delete_baseline),
+        )
+        .route("/projects/:id/regressions", get(baselines::get_regressions))
+        .route("/projects/:id/flaky", get(flaky::get_flaky))
+        .route(
+            "/projects/:id/test_cases/:name/history",
+            get(history::get_history),
+        )
+        .route(
+            "/test_runs",
+            post(test_runs::add_test_run).get(test_runs::get_test_runs),
+        )
+        .route(
+            "/test_runs/:id",
+            get(test_runs::get_single_test_run)
+                .patch(test_runs::update_test_run)
+                .delete(test_runs::delete_test_run),
+        )
+        .route(
+            "/test_runs/:id/reports",
+            get(test_runs::get_reports_for_test_run),
+        )
+        .fallback(not_found);
+    let router = if features.import {
+        router
+            .route("/import/junit", post(import::import_junit))
+            .route("/import/atx", post(import::import_atx))
+            .route("/import/libtest", post(import::import_libtest))
+    } else {
+        router
+    };
+    router
+        .with_state(AppState {
+            db,
+            features,
+            blobs,
+        })
+        .layer(middleware::map_response(error::method_not_allowed))
+}
+
+async fn not_found() -> RgError {
+    RgError::from(eyre!("No such endpoint")).with_status_code(StatusCode::NOT_FOUND)
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    init_tracing();
-    let db = Database::connect(DB_URL).await?;
+    let config = Config::load(Cli::parse())?;
+    init_tracing(&config.log_level)?;
+    tracing::info!("Configuration:\n{}", config.to_redacted_toml()?);
+
+    let db = Database::connect(&config.database_url).await?;
 
     let schema_manager = SchemaManager::new(&db);
 
     migrator::Migrator::up(&db, None).await?;
     assert!(schema_manager.has_table("report").await?);
 
-    let app = app(db);
+    let blobs = Arc::new(LocalBlobStore::new(&config.attachment_dir));
+    let app = app(db, config.features, blobs, config.max_attachment_size()?);
 
-    axum::Server::bind(&"127.0.0.1:3000".parse().unwrap())
+    axum::Server::bind(&config.socket_addr())
         .serve(app.into_make_service())
         .await?;
 
@@ -58,31 +205,241 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
 async fn add_report(
     State(db): State<DatabaseConnection>,
+    State(features): State<Features>,
     report: String,
 ) -> Result<String, RgError> {
-    let report: report::Model =
-        serde_json::from_str(&report).with_status_code(StatusCode::BAD_REQUEST)?;
+    let report: report::Model = extract::json(&report)?;
+
+    validate_duration(&report)?;
+    report_metadata::validate(report.properties.keys(), &report.tags)?;
+    let project = projects::find_by_name(&db, features, &report.project).await?;
+    if let Some(test_run_id) = report.test_run_id {
+        test_runs::check_report_run(&db, test_run_id, project.id).await?;
+    }
+
+    let id = db
+        .transaction(|txn| {
+            Box::pin(async move {
+                let created = new_report_model(&report, &project).insert(txn).await?;
+                let id = created.id;
+                store_metadata(txn, id, report).await?;
+                baselines::compare(txn, created).await?;
+                Ok::<_, DbErr>(id)
+            })
+        })
+        .await?;
+    Ok(id.to_string())
+}
+
+/// Rejects negative durations of posted reports, like
+/// [`extract::duration_ms`] does for the other structs.
+fn validate_duration(report: &report::Model) -> Result<(), RgError> {
+    match report.duration_ms {
+        Some(ms) if ms < 0 => Err(RgError::from(eyre!(
+            "invalid value: integer `{ms}`, expected a duration of at least 0 ms"
+        ))
+        .with_status_code(StatusCode::BAD_REQUEST)
+        .with_details(ErrorDetails {
+            field: Some("duration_ms".to_string()),
+        })),
+        _ => Ok(()),
+    }
+}
+
+/// A test step to be created, optionally together with its sub-steps
+#[derive(Deserialize)]
+struct NewTestStep {
+    name: String,
+    step_number: i32,
+    date: DateTimeWithTimeZone,
+    verdict: Verdict,
+    #[serde(default, deserialize_with = "extract::duration_ms")]
+    duration_ms: Option<i64>,
+    #[serde(default)]
+    output: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    expected: Option<String>,
+    #[serde(default)]
+    actual: Option<String>,
+    #[serde(default)]
+    unit: Option<String>,
+    #[serde(default)]
+    failure_category: Option<FailureCategory>,
+    /// Existing step of the same report this step is a sub-step of
+    #[serde(default)]
+    parent_id: Option<i32>,
+    #[serde(default)]
+    children: Vec<NewTestStep>,
+    #[serde(default)]
+    measurements: Vec<NewMeasurement>,
+}
+
+/// A report together with all its test steps, which may be nested
+#[derive(Deserialize)]
+struct NewReportWithSteps {
+    #[serde(flatten)]
+    report: report::Model,
+    #[serde(default)]
+    test_steps: Vec<NewTestStep>,
+}
+
+/// A report together with all its test steps
+#[derive(Serialize)]
+struct ReportWithSteps {
+    #[serde(flatten)]
+    report: report::Model,
+    test_steps: Vec<test_step::Model>,
+}
+
+/// Creates a report with all its test steps at once, either everything or
+/// nothing gets stored.
+async fn add_report_with_steps(
+    State(db): State<DatabaseConnection>,
+    State(features): State<Features>,
+    report: String,
+) -> Result<Json<ReportWithSteps>, RgError> {
+    let NewReportWithSteps { report, test_steps } = extract::json(&report)?;
+    if test_steps.iter().any(|ts| ts.parent_id.is_some()) {
+        return Err(RgError::from(eyre!(
+            "Test steps of a new report cannot have a parent_id, nest them in children instead!"
+        ))
+        .with_status_code(StatusCode::BAD_REQUEST)
+        .with_details(ErrorDetails {
+            field: Some("test_steps".to_string()),
+        }));
+    }
+    validate_duration(&report)?;
+    report_metadata::validate(report.properties.keys(), &report.tags)?;
+    let project = projects::find_by_name(&db, features, &report.project).await?;
+    if let Some(test_run_id) = report.test_run_id {
+        test_runs::check_report_run(&db, test_run_id, project.id).await?;
+    }
+
+    let created = db
+        .transaction(|txn| {
+            Box::pin(async move {
+                let created = new_report_model(&report, &project).insert(txn).await?;
+                store_metadata(txn, created.id, report).await?;
+                insert_test_steps(txn, created.id, None, test_steps).await?;
+                let report = update_computed_verdict(txn, created.id).await?;
+                let report = baselines::compare(txn, report).await?;
+                let steps = report
+                    .find_related(TestStep)
+                    .order_by_asc(test_step::Column::Id)
+                    .all(txn)
+                    .await?;
+                let report = report_metadata::load_one(txn, report).await?;
+                Ok::<_, DbErr>(ReportWithSteps {
+                    report: report.with_original_offset(),
+                    test_steps: steps
+                        .into_iter()
+                        .map(test_step::Model::with_original_offset)
+                        .collect(),
+                })
+            })
+        })
+        .await?;
+    Ok(Json(created))
+}
 
-    let report_model = report::ActiveModel {
-        date: ActiveValue::Set(report.date),
-        project: ActiveValue::Set(report.project),
-        name: ActiveValue::Set(report.name),
+fn new_report_model(report: &report::Model, project: &project::Model) -> report::ActiveModel {
+    report::ActiveModel {
+        date: ActiveValue::Set(date::to_utc(report.date)),
+        date_offset: ActiveValue::Set(date::offset_seconds(&report.date)),
+        project: ActiveValue::Set(project.name.clone()),
+        project_id: ActiveValue::Set(Some(project.id)),
+        duration_ms: ActiveValue::Set(report.duration_ms),
+        output: ActiveValue::Set(report.output.clone()),
+        name: ActiveValue::Set(report.name.clone()),
         verdict: ActiveValue::Set(report.verdict),
+        declared_verdict: ActiveValue::Set(report.verdict),
+        derive_verdict: ActiveValue::Set(report.derive_verdict),
+        test_run_id: ActiveValue::Set(report.test_run_id),
         ..Default::default()
-    };
+    }
+}
+
+/// Stores the properties and tags of a newly created report.
+async fn store_metadata<C: ConnectionTrait>(
+    db: &C,
+    report_id: i32,
+    report: report::Model,
+) -> Result<(), DbErr> {
+    let properties = report
+        .properties
+        .into_iter()
+        .map(|(key, value)| (key, Some(value)))
+        .collect();
+    report_metadata::store_properties(db, report_id, properties, true).await?;
+    report_metadata::store_tags(db, report_id, &report.tags).await
+}
+
+fn new_test_step_model(
+    ts: &NewTestStep,
+    report_id: i32,
+    parent_id: Option<i32>,
+) -> test_step::ActiveModel {
+    test_step::ActiveModel {
+        name: ActiveValue::Set(ts.name.clone()),
+        step_number: ActiveValue::Set(ts.step_number),
+        date: ActiveValue::Set(date::to_utc(ts.date)),
+        date_offset: ActiveValue::Set(date::offset_seconds(&ts.date)),
+        verdict: ActiveValue::Set(ts.verdict),
+        report_id: ActiveValue::Set(report_id),
+        parent_id: ActiveValue::Set(parent_id),
+        duration_ms: ActiveValue::Set(ts.duration_ms),
+        output: ActiveValue::Set(ts.output.clone()),
+        message: ActiveValue::Set(ts.message.clone()),
+        expected: ActiveValue::Set(ts.expected.clone()),
+        actual: ActiveValue::Set(ts.actual.clone()),
+        unit: ActiveValue::Set(ts.unit.clone()),
+        failure_category: ActiveValue::Set(ts.failure_category),
+        ..Default::default()
+    }
+}
 
-    let res = Report::insert(report_model).exec(&db).await?;
-    Ok(res.last_insert_id.to_string())
+/// Inserts the steps with all their sub-steps and measurements below
+/// `parent_id` and returns the ids of the created steps, parents before their
+/// children.
+async fn insert_test_steps<C: ConnectionTrait>(
+    db: &C,
+    report_id: i32,
+    parent_id: Option<i32>,
+    steps: Vec<NewTestStep>,
+) -> Result<Vec<i32>, DbErr> {
+    let mut ids = Vec::new();
+    let mut pending: Vec<_> = steps.into_iter().rev().map(|ts| (parent_id, ts)).collect();
+    while let Some((parent_id, ts)) = pending.pop() {
+        let id = TestStep::insert(new_test_step_model(&ts, report_id, parent_id))
+            .exec(db)
+            .await?
+            .last_insert_id;
+        measurements::insert_measurements(db, id, ts.measurements).await?;
+        ids.push(id);
+        pending.extend(ts.children.into_iter().rev().map(|child| (Some(id), child)));
+    }
+    Ok(ids)
 }
 
 async fn get_reports(
+    Query(mut filter): Query<ReportFilter>,
+    RawQuery(query): RawQuery,
     State(db): State<DatabaseConnection>,
-) -> Result<Json<Vec<report::Model>>, RgError> {
-    Ok(Json(
-        Report::find()
-            .all(&db)
-            .await
-            .with_status_code(StatusCode::INTERNAL_SERVER_ERROR)?,
+) -> Result<([(&'static str, String); 1], Json<Vec<report::Model>>), RgError> {
+    filter.properties = filter::property_filters(query.as_deref());
+    let select = filter.select();
+    let total_count = select.clone().count(&db).await?;
+    let reports = filter.paginate(select).all(&db).await?;
+    let reports = report_metadata::load(&db, reports)
+        .await?
+        .into_iter()
+        .map(report::Model::with_original_offset)
+        .collect();
+    Ok((
+        [(filter::TOTAL_COUNT_HEADER, total_count.to_string())],
+        Json(reports),
     ))
 }
 
@@ -90,116 +447,527 @@ async fn get_single_report(
     Path(report_id): Path<i32>,
     State(db): State<DatabaseConnection>,
 ) -> Result<Json<report::Model>, RgError> {
-    match Report::find_by_id(report_id).one(&db).await {
-        Ok(None) => Err(eyre!("Could not find report")).with_status_code(StatusCode::NOT_FOUND),
-        Ok(Some(x)) => Ok(Json(x)),
-        Err(e) => Err(RgError::from(e).with_status_code(StatusCode::INTERNAL_SERVER_ERROR)),
-    }
+    let report = find_report(&db, report_id).await?;
+    Ok(Json(report_metadata::load_one(&db, report).await?))
+}
+
+async fn find_report<C: ConnectionTrait>(db: &C, report_id: i32) -> Result<report::Model, RgError> {
+    Report::find_by_id(report_id)
+        .one(db)
+        .await?
+        .map(report::Model::with_original_offset)
+        .ok_or_else(|| eyre!("Could not find report with id {}!", report_id))
+        .with_status_code(StatusCode::NOT_FOUND)
 }
 
 async fn add_teststep(
     Path(report_id): Path<i32>,
     State(db): State<DatabaseConnection>,
-    Json(ts): Json<test_step::Model>,
-) -> (StatusCode, String) {
-    match Report::find_by_id(report_id).one(&db).await {
-        Ok(Some(report)) => {
-            let ts_model = test_step::ActiveModel {
-                name: ActiveValue::Set(ts.name),
-                step_number: ActiveValue::Set(ts.step_number),
-                date: ActiveValue::Set(ts.date),
-                verdict: ActiveValue::Set(ts.verdict),
-                report_id: ActiveValue::Set(report.id),
-                ..Default::default()
-            };
-            match TestStep::insert(ts_model).exec(&db).await {
-                Ok(res) => (StatusCode::OK, res.last_insert_id.to_string()),
-                Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, String::new()),
-            }
+    ts: String,
+) -> Result<String, RgError> {
+    let mut ts: NewTestStep = extract::json(&ts)?;
+    let report = find_report(&db, report_id).await?;
+    let parent_id = ts.parent_id.take();
+    if let Some(parent_id) = parent_id {
+        let parent = TestStep::find_by_id(parent_id).one(&db).await?;
+        if parent.map(|parent| parent.report_id) != Some(report.id) {
+            return Err(RgError::from(eyre!(
+                "Could not find test step with id {} in report {}!",
+                parent_id,
+                report.id
+            ))
+            .with_status_code(StatusCode::BAD_REQUEST)
+            .with_details(ErrorDetails {
+                field: Some("parent_id".to_string()),
+            }));
         }
-        Ok(None) => (
-            StatusCode::NOT_FOUND,
-            format!("Could not find report with id {}!", report_id),
-        ),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, String::new()),
     }
+    let id = db
+        .transaction(|txn| {
+            Box::pin(async move {
+                let ids = insert_test_steps(txn, report.id, parent_id, vec![ts]).await?;
+                let report = update_computed_verdict(txn, report.id).await?;
+                baselines::compare(txn, report).await?;
+                Ok::<_, DbErr>(ids[0])
+            })
+        })
+        .await?;
+    Ok(id.to_string())
 }
 
-async fn get_single_teststep(
-    Path(teststp_id): Path<i32>,
+/// Changes to a report, fields that are `None` are left untouched and
+/// nullable fields set to `null` are cleared
+#[derive(Deserialize)]
+struct ReportPatch {
+    date: Option<DateTimeWithTimeZone>,
+    #[serde(default, deserialize_with = "extract::nullable_duration_ms")]
+    duration_ms: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    output: Option<Option<String>>,
+    project: Option<String>,
+    name: Option<String>,
+    verdict: Option<Verdict>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    derive_verdict: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    test_run_id: Option<Option<i32>>,
+    /// Properties to set, `null` values remove the property
+    properties: Option<BTreeMap<String, Option<String>>>,
+    /// Replaces all tags
+    tags: Option<Vec<String>>,
+    /// Whether properties that are not mentioned get removed
+    #[serde(skip)]
+    replace_properties: bool,
+}
+
+impl From<report::Model> for ReportPatch {
+    fn from(report: report::Model) -> Self {
+        Self {
+            date: Some(report.date),
+            duration_ms: Some(report.duration_ms),
+            output: Some(report.output),
+            project: Some(report.project),
+            name: Some(report.name),
+            verdict: Some(report.verdict),
+            derive_verdict: Some(report.derive_verdict),
+            test_run_id: Some(report.test_run_id),
+            properties: Some(
+                report
+                    .properties
+                    .into_iter()
+                    .map(|(key, value)| (key, Some(value)))
+                    .collect(),
+            ),
+            tags: Some(report.tags),
+            replace_properties: true,
+        }
+    }
+}
+
+async fn replace_report(
+    Path(report_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+    State(features): State<Features>,
+    report: String,
+) -> Result<Json<report::Model>, RgError> {
+    let report: report::Model = extract::json(&report)?;
+    validate_duration(&report)?;
+    Ok(Json(
+        patch_report(&db, features, report_id, report.into()).await?,
+    ))
+}
+
+async fn update_report(
+    Path(report_id): Path<i32>,
     State(db): State<DatabaseConnection>,
-) -> Result<Json<Option<test_step::Model>>, StatusCode> {
-    match TestStep::find_by_id(teststp_id).one(&db).await {
-        Ok(res) => Ok(Json(res)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    State(features): State<Features>,
+    patch: String,
+) -> Result<Json<report::Model>, RgError> {
+    let patch: ReportPatch = extract::json(&patch)?;
+    Ok(Json(patch_report(&db, features, report_id, patch).await?))
+}
+
+async fn patch_report(
+    db: &DatabaseConnection,
+    features: Features,
+    report_id: i32,
+    patch: ReportPatch,
+) -> Result<report::Model, RgError> {
+    report_metadata::validate(
+        patch.properties.iter().flat_map(BTreeMap::keys),
+        patch.tags.as_deref().unwrap_or_default(),
+    )?;
+    let report = find_report(db, report_id).await?;
+    let project = match patch.project {
+        Some(project) => Some(projects::find_by_name(db, features, &project).await?),
+        None => None,
+    };
+    // Moving the report to another project must not leave it in a run of
+    // the old project.
+    if patch.test_run_id.is_some() || project.is_some() {
+        let test_run_id = patch.test_run_id.unwrap_or(report.test_run_id);
+        let project_id = project
+            .as_ref()
+            .map(|project| project.id)
+            .or(report.project_id);
+        if let (Some(test_run_id), Some(project_id)) = (test_run_id, project_id) {
+            test_runs::check_report_run(db, test_run_id, project_id).await?;
+        }
+    }
+
+    let mut report_model: report::ActiveModel = report.into();
+    if let Some(date) = patch.date {
+        report_model.date = ActiveValue::Set(date::to_utc(date));
+        report_model.date_offset = ActiveValue::Set(date::offset_seconds(&date));
+    }
+    if let Some(duration_ms) = patch.duration_ms {
+        report_model.duration_ms = ActiveValue::Set(duration_ms);
+    }
+    if let Some(output) = patch.output {
+        report_model.output = ActiveValue::Set(output);
+    }
+    if let Some(project) = project {
+        report_model.project = ActiveValue::Set(project.name);
+        report_model.project_id = ActiveValue::Set(Some(project.id));
+    }
+    if let Some(name) = patch.name {
+        report_model.name = ActiveValue::Set(name);
+    }
+    if let Some(verdict) = patch.verdict {
+        report_model.declared_verdict = ActiveValue::Set(verdict);
     }
+    if let Some(derive_verdict) = patch.derive_verdict {
+        report_model.derive_verdict = ActiveValue::Set(derive_verdict);
+    }
+    if let Some(test_run_id) = patch.test_run_id {
+        report_model.test_run_id = ActiveValue::Set(test_run_id);
+    }
+
+    Ok(db
+        .transaction(|txn| {
+            Box::pin(async move {
+                let report = report_model.update(txn).await?;
+                if let Some(properties) = patch.properties {
+                    report_metadata::store_properties(
+                        txn,
+                        report.id,
+                        properties,
+                        patch.replace_properties,
+                    )
+                    .await?;
+                }
+                if let Some(tags) = patch.tags {
+                    report_metadata::store_tags(txn, report.id, &tags).await?;
+                }
+                let report = update_computed_verdict(txn, report.id).await?;
+                let report = baselines::compare(txn, report).await?;
+                report_metadata::load_one(txn, report).await
+            })
+        })
+        .await?
+        .with_original_offset())
 }
 
-async fn get_teststeps(
+/// Changes to a test step, fields that are `None` are left untouched and
+/// nullable fields set to `null` are cleared
+#[derive(Deserialize)]
+struct TestStepPatch {
+    name: Option<String>,
+    step_number: Option<i32>,
+    date: Option<DateTimeWithTimeZone>,
+    verdict: Option<Verdict>,
+    #[serde(default, deserialize_with = "extract::nullable_duration_ms")]
+    duration_ms: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    output: Option<Option<String>>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    message: Option<Option<String>>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    expected: Option<Option<String>>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    actual: Option<Option<String>>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    unit: Option<Option<String>>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    failure_category: Option<Option<FailureCategory>>,
+}
+
+async fn find_teststep<C: ConnectionTrait>(
+    db: &C,
+    teststep_id: i32,
+) -> Result<test_step::Model, RgError> {
+    TestStep::find_by_id(teststep_id)
+        .one(db)
+        .await?
+        .map(test_step::Model::with_original_offset)
+        .ok_or_else(|| eyre!("Could not find test step with id {}!", teststep_id))
+        .with_status_code(StatusCode::NOT_FOUND)
+}
+
+async fn update_teststep(
+    Path(teststep_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+    patch: String,
+) -> Result<Json<test_step::Model>, RgError> {
+    let patch: TestStepPatch = extract::json(&patch)?;
+    let ts = find_teststep(&db, teststep_id).await?;
+
+    let mut ts_model: test_step::ActiveModel = ts.into();
+    if let Some(name) = patch.name {
+        ts_model.name = ActiveValue::Set(name);
+    }
+    if let Some(step_number) = patch.step_number {
+        ts_model.step_number = ActiveValue::Set(step_number);
+    }
+    if let Some(date) = patch.date {
+        ts_model.date = ActiveValue::Set(date::to_utc(date));
+        ts_model.date_offset = ActiveValue::Set(date::offset_seconds(&date));
+    }
+    if let Some(verdict) = patch.verdict {
+        ts_model.verdict = ActiveValue::Set(verdict);
+    }
+    if let Some(duration_ms) = patch.duration_ms {
+        ts_model.duration_ms = ActiveValue::Set(duration_ms);
+    }
+    if let Some(output) = patch.output {
+        ts_model.output = ActiveValue::Set(output);
+    }
+    if let Some(message) = patch.message {
+        ts_model.message = ActiveValue::Set(message);
+    }
+    if let Some(expected) = patch.expected {
+        ts_model.expected = ActiveValue::Set(expected);
+    }
+    if let Some(actual) = patch.actual {
+        ts_model.actual = ActiveValue::Set(actual);
+    }
+    if let Some(unit) = patch.unit {
+        ts_model.unit = ActiveValue::Set(unit);
+    }
+    if let Some(failure_category) = patch.failure_category {
+        ts_model.failure_category = ActiveValue::Set(failure_category);
+    }
+
+    Ok(Json(
+        db.transaction(|txn| {
+            Box::pin(async move {
+                let ts = ts_model.update(txn).await?;
+                let report = update_computed_verdict(txn, ts.report_id).await?;
+                baselines::compare(txn, report).await?;
+                Ok::<_, DbErr>(ts)
+            })
+        })
+        .await?
+        .with_original_offset(),
+    ))
+}
+
+async fn delete_teststep(
+    Path(teststep_id): Path<i32>,
     State(db): State<DatabaseConnection>,
-) -> Result<Json<Vec<test_step::Model>>, StatusCode> {
-    match TestStep::find().all(&db).await {
-        Ok(res) => Ok(Json(res)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    State(blobs): State<Arc<dyn BlobStore>>,
+) -> Result<(), RgError> {
+    let ts = find_teststep(&db, teststep_id).await?;
+    let blob_keys = db
+        .transaction(|txn| {
+            Box::pin(async move {
+                let steps = TestStep::find()
+                    .filter(test_step::Column::ReportId.eq(ts.report_id))
+                    .all(txn)
+                    .await?;
+                let ids = subtree_ids(&steps, ts.id);
+                let blob_keys = attachments::delete_where(
+                    txn,
+                    attachment::Column::TestStepId.is_in(ids.clone()),
+                )
+                .await?;
+                Measurement::delete_many()
+                    .filter(measurement::Column::TestStepId.is_in(ids.clone()))
+                    .exec(txn)
+                    .await?;
+                Triage::delete_many()
+                    .filter(entities::triage::Column::TestStepId.is_in(ids.clone()))
+                    .exec(txn)
+                    .await?;
+                TestStep::delete_many()
+                    .filter(test_step::Column::Id.is_in(ids))
+                    .exec(txn)
+                    .await?;
+                let report = update_computed_verdict(txn, ts.report_id).await?;
+                baselines::compare(txn, report).await?;
+                Ok::<_, DbErr>(blob_keys)
+            })
+        })
+        .await?;
+    attachments::delete_unused_blobs(&db, blobs.as_ref(), blob_keys).await;
+    Ok(())
+}
+
+/// Ids of the step `root_id` and all its direct and indirect sub-steps
+fn subtree_ids(steps: &[test_step::Model], root_id: i32) -> Vec<i32> {
+    let mut ids = vec![root_id];
+    let mut i = 0;
+    while let Some(&id) = ids.get(i) {
+        ids.extend(
+            steps
+                .iter()
+                .filter(|step| step.parent_id == Some(id))
+                .map(|step| step.id),
+        );
+        i += 1;
     }
+    ids
+}
+
+/// Recomputes the verdict of a report from its test steps.
+///
+/// The computed verdict is always stored, the effective verdict of the report
+/// only follows it if the report or its project has `derive_verdict` set.
+/// The report is read in the given transaction and locked, so a concurrent
+/// change of `derive_verdict` cannot be overwritten with a stale verdict.
+async fn update_computed_verdict<C: ConnectionTrait>(
+    db: &C,
+    report_id: i32,
+) -> Result<report::Model, DbErr> {
+    let report = Report::find_by_id(report_id)
+        .lock_exclusive()
+        .one(db)
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound(format!("Report {report_id}")))?;
+    let steps = report.find_related(TestStep).all(db).await?;
+    let computed_verdict =
+        (!steps.is_empty()).then(|| Verdict::worst(steps.iter().map(|step| step.verdict)));
+    let verdict = match computed_verdict {
+        Some(computed_verdict) if projects::derives_verdict(db, &report).await? => computed_verdict,
+        _ => report.declared_verdict,
+    };
+
+    let mut report_model: report::ActiveModel = report.into();
+    report_model.computed_verdict = ActiveValue::Set(computed_verdict);
+    report_model.verdict = ActiveValue::Set(verdict);
+    report_model.update(db).await
+}
+
+async fn get_single_teststep(
+    Path(teststep_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<test_step::Model>, RgError> {
+    Ok(Json(find_teststep(&db, teststep_id).await?))
+}
+
+async fn get_teststeps(
+    Query(filter): Query<TestStepFilter>,
+    State(db): State<DatabaseConnection>,
+) -> Result<([(&'static str, String); 1], Json<Vec<test_step::Model>>), RgError> {
+    let select = filter.select();
+    let total_count = select.clone().count(&db).await?;
+    let test_steps = filter.paginate(select).all(&db).await?;
+    let test_steps = test_steps
+        .into_iter()
+        .map(test_step::Model::with_original_offset)
+        .collect();
+    Ok((
+        [(filter::TOTAL_COUNT_HEADER, total_count.to_string())],
+        Json(test_steps),
+    ))
 }
 
 async fn get_teststeps_for_report(
     Path(report_id): Path<i32>,
     State(db): State<DatabaseConnection>,
-) -> Result<Json<Vec<test_step::Model>>, StatusCode> {
-    match Report::find_by_id(report_id).one(&db).await {
-        Ok(Some(report)) => match report.find_related(TestStep).all(&db).await {
-            Ok(res) => Ok(Json(res)),
-            Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-        },
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+) -> Result<Json<Vec<test_step::Model>>, RgError> {
+    let report = find_report(&db, report_id).await?;
+    let test_steps = report.find_related(TestStep).all(&db).await?;
+    Ok(Json(
+        test_steps
+            .into_iter()
+            .map(test_step::Model::with_original_offset)
+            .collect(),
+    ))
+}
+
+/// Returns the test steps of a report arranged as trees of sub-steps.
+async fn get_teststep_tree(
+    Path(report_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<test_step::TreeNode>>, RgError> {
+    let report = find_report(&db, report_id).await?;
+    let test_steps = report.find_related(TestStep).all(&db).await?;
+    Ok(Json(test_step::TreeNode::build(
+        test_steps
+            .into_iter()
+            .map(test_step::Model::with_original_offset)
+            .collect(),
+    )))
+}
+
+/// Compares the test steps of a report with those of another, usually
+/// later, report.
+async fn get_report_diff(
+    Path((report_id, other_id)): Path<(i32, i32)>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<diff::ReportDiff>, RgError> {
+    let old = find_report(&db, report_id).await?;
+    let new = find_report(&db, other_id).await?;
+    let old_steps = old.find_related(TestStep).all(&db).await?;
+    let new_steps = new.find_related(TestStep).all(&db).await?;
+    let old = report_metadata::load_one(&db, old).await?;
+    let new = report_metadata::load_one(&db, new).await?;
+    Ok(Json(diff::ReportDiff::compute(
+        old,
+        old_steps
+            .into_iter()
+            .map(test_step::Model::with_original_offset)
+            .collect(),
+        new,
+        new_steps
+            .into_iter()
+            .map(test_step::Model::with_original_offset)
+            .collect(),
+    )))
 }
 
 async fn delete_report(
     Path(report_id): Path<i32>,
     State(db): State<DatabaseConnection>,
-) -> (StatusCode, String) {
-    match Report::find_by_id(report_id).one(&db).await {
-        Ok(Some(report)) => match db
-            .transaction(|txn| {
-                Box::pin(async move {
-                    test_step::Entity::delete_many()
-                        .filter(test_step::Column::ReportId.eq(report.id))
-                        .exec(txn)
+    State(blobs): State<Arc<dyn BlobStore>>,
+) -> Result<(), RgError> {
+    let report = find_report(&db, report_id).await?;
+    let blob_keys = db
+        .transaction(|txn| {
+            Box::pin(async move {
+                let blob_keys =
+                    attachments::delete_where(txn, attachment::Column::ReportId.eq(report.id))
                         .await?;
-                    report.delete(txn).await
-                })
+                Measurement::delete_many()
+                    .filter(
+                        measurement::Column::TestStepId.in_subquery(
+                            sea_query::Query::select()
+                                .column(test_step::Column::Id)
+                                .from(TestStep)
+                                .and_where(test_step::Column::ReportId.eq(report.id))
+                                .to_owned(),
+                        ),
+                    )
+                    .exec(txn)
+                    .await?;
+                Triage::delete_many()
+                    .filter(entities::triage::Column::ReportId.eq(report.id))
+                    .exec(txn)
+                    .await?;
+                test_step::Entity::delete_many()
+                    .filter(test_step::Column::ReportId.eq(report.id))
+                    .exec(txn)
+                    .await?;
+                report_metadata::delete(txn, report.id).await?;
+                baselines::unset(txn, project::Column::BaselineReportId, report.id).await?;
+                report.delete(txn).await?;
+                Ok::<_, DbErr>(blob_keys)
             })
-            .await
-        {
-            Ok(_) => (StatusCode::OK, String::new()),
-            Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, String::new()),
-        },
-        Ok(None) => (
-            StatusCode::NOT_FOUND,
-            format!("Could not find report with id {}!", report_id),
-        ),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, String::new()),
-    }
+        })
+        .await?;
+    attachments::delete_unused_blobs(&db, blobs.as_ref(), blob_keys).await;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::blob_store::MemoryBlobStore;
     use axum::http;
     use axum::http::Request;
+    use hyper::body::Bytes;
     use hyper::Body;
+    use sea_orm::DbBackend;
     use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use temp_file::TempFile;
     use tower::{Service, ServiceExt};
 
     #[tokio::test]
     async fn test_error_cases_for_add_report_with_wrong_format() {
         let (db, _tmp_file) = setup_empty_temp_database().await;
-        let mut app = app(db);
+        let mut app = test_app(db, Features::default());
 
         // Add report
         let response = app
@@ -223,28 +991,75 @@ mod tests {
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
         let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
         assert_eq!(
-            body,
-            "input contains invalid characters at line 1 column 15"
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            json!({"code": 400,
+                "message": "input contains invalid characters at line 1 column 15",
+                "details": {"field": "date"}})
         );
     }
 
-    async fn setup_empty_temp_database() -> (DatabaseConnection, TempFile) {
-        let tmp_file = temp_file::empty();
+    /// Connects to a new database, either a temporary SQLite file or, if
+    /// `RESULT_GUIDE_TEST_POSTGRES_URL` is set, a new database on that
+    /// PostgreSQL server (see `scripts/test-postgres.sh`).
+    async fn connect_temp_database() -> (DatabaseConnection, Option<TempFile>) {
+        static DATABASE_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
-        let db = Database::connect(format!(
-            "sqlite:{}?mode=rwc",
-            tmp_file.path().to_str().unwrap()
-        ))
-        .await
-        .unwrap();
+        match std::env::var("RESULT_GUIDE_TEST_POSTGRES_URL") {
+            Ok(server_url) => {
+                let server_url = server_url.trim_end_matches('/');
+                let name = format!(
+                    "result_guide_test_{}_{}",
+                    std::process::id(),
+                    DATABASE_COUNTER.fetch_add(1, Ordering::Relaxed)
+                );
+                let server = Database::connect(server_url).await.unwrap();
+                server
+                    .execute_unprepared(&format!("DROP DATABASE IF EXISTS {name}"))
+                    .await
+                    .unwrap();
+                server
+                    .execute_unprepared(&format!("CREATE DATABASE {name}"))
+                    .await
+                    .unwrap();
+                let db = Database::connect(format!("{server_url}/{name}"))
+                    .await
+                    .unwrap();
+                (db, None)
+            }
+            Err(_) => {
+                let tmp_file = temp_file::empty();
+                let db = Database::connect(format!(
+                    "sqlite:{}?mode=rwc",
+                    tmp_file.path().to_str().unwrap()
+                ))
+                .await
+                .unwrap();
+                (db, Some(tmp_file))
+            }
+        }
+    }
+
+    async fn setup_empty_temp_database() -> (DatabaseConnection, Option<TempFile>) {
+        let (db, tmp_file) = connect_temp_database().await;
         migrator::Migrator::fresh(&db).await.unwrap();
         (db, tmp_file)
     }
 
+    /// The app with attachments kept in memory
+    fn test_app(db: DatabaseConnection, features: Features) -> Router {
+        app(
+            db,
+            features,
+            Arc::new(MemoryBlobStore::default()),
+            1024 * 1024,
+        )
+    }
+
     #[tokio::test]
     async fn test_report() {
         let (db, _tmp_file) = setup_empty_temp_database().await;
-        let mut app = app(db);
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "TestProjekt").await;
 
         // Add report
         app.call(
@@ -257,7 +1072,7 @@ mod tests {
                         {"date": "2014-11-28T21:00:09+09:00",
                         "project": "TestProjekt",
                         "name": "TestReport",
-                        "verdict": "PASSED"}))
+                        "verdict": "SUCCESS"}))
                     .unwrap(),
                 ))
                 .unwrap(),
@@ -283,15 +1098,24 @@ mod tests {
         let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
         assert_eq!(
             serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
-            // TODO: Find the reason why the returned timestamp is in UTC
             json!(
                 {
                     "date":
-                    "2014-11-28T12:00:09+00:00",
+                    "2014-11-28T21:00:09+09:00",
+                    "duration_ms": null,
+                    "output": null,
                     "id": 1,
                     "project": "TestProjekt",
+                    "project_id": 1,
                     "name": "TestReport",
-                    "verdict": "PASSED"
+                    "verdict": "SUCCESS",
+                    "declared_verdict": "SUCCESS",
+                    "computed_verdict": null,
+                    "derive_verdict": null,
+                    "test_run_id": null,
+                    "baseline_status": null,
+                    "properties": {},
+                    "tags": []
             })
         );
 
@@ -328,7 +1152,10 @@ mod tests {
             .unwrap();
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
         let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-        assert_eq!(body, "Could not find report with id 1!");
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            json!({"code": 404, "message": "Could not find report with id 1!"})
+        );
 
         // Check that report got deleted
         let response = app
@@ -346,6 +1173,2556 @@ mod tests {
             .unwrap();
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
         let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-        assert_eq!(body, "Could not find report");
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            json!({"code": 404, "message": "Could not find report with id 1!"})
+        );
+    }
+
+    async fn send(
+        app: &mut Router,
+        method: http::Method,
+        uri: &str,
+        body: Body,
+    ) -> (StatusCode, Bytes) {
+        let response = app
+            .ready()
+            .await
+            .unwrap()
+            .call(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(body)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, body)
+    }
+
+    async fn post_json(
+        app: &mut Router,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (StatusCode, Bytes) {
+        send(
+            app,
+            http::Method::POST,
+            uri,
+            Body::from(serde_json::to_vec(&body).unwrap()),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_unknown_verdicts_are_rejected() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "TestProjekt").await;
+
+        let (status, body) = post_json(
+            &mut app,
+            "/reports",
+            json!({"date": "2014-11-28T21:00:09+09:00",
+                "project": "TestProjekt",
+                "name": "TestReport",
+                "verdict": "PASSED"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(error["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("unknown variant `PASSED`"));
+        assert_eq!(error["details"]["field"], "verdict");
+
+        let (status, _) = post_json(
+            &mut app,
+            "/reports",
+            json!({"date": "2014-11-28T21:00:09+09:00",
+                "project": "TestProjekt",
+                "name": "TestReport",
+                "verdict": "SUCCESS"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = post_json(
+            &mut app,
+            "/reports/1/test_steps",
+            json!({"name": "Bus Lesen",
+                "step_number": 1,
+                "date": "2014-11-28T21:00:01+09:00",
+                "verdict": "passed"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(error["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("unknown variant `passed`"));
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_json() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "TestProjekt").await;
+
+        let (status, body) = post_json(
+            &mut app,
+            "/reports/bulk",
+            json!({"date": "2014-11-28T21:00:09+09:00",
+            "project": "TestProjekt",
+            "name": "TestReport",
+            "verdict": "SUCCESS",
+            "test_steps": [
+                {"name": "Bus Lesen",
+                "step_number": 1,
+                "date": "2014-11-28T21:00:01+09:00",
+                "verdict": "SUCCESS"},
+                {"name": "Mess Lesen",
+                "step_number": "two",
+                "date": "2014-11-28T21:00:01+09:00",
+                "verdict": "SUCCESS"}
+            ]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["code"], 400);
+        assert_eq!(error["details"]["field"], "test_steps[1].step_number");
+
+        // Durations must not be negative
+        let step = json!({"name": "Bus Lesen",
+            "step_number": 1,
+            "date": "2014-11-28T21:00:01+09:00",
+            "verdict": "SUCCESS"});
+        let mut child = step.clone();
+        child["duration_ms"] = json!(-1);
+        let mut parent = step.clone();
+        parent["children"] = json!([child]);
+        let report = json!({"date": "2014-11-28T21:00:09+09:00",
+            "project": "TestProjekt",
+            "name": "TestReport",
+            "verdict": "SUCCESS"});
+        let mut negative_report = report.clone();
+        negative_report["duration_ms"] = json!(-5);
+        let mut nested_step = report.clone();
+        nested_step["test_steps"] = json!([parent]);
+        for (uri, body, field) in [
+            ("/reports", negative_report.clone(), "duration_ms"),
+            ("/reports/bulk", negative_report, "duration_ms"),
+            (
+                "/reports/bulk",
+                nested_step,
+                "test_steps[0].children[0].duration_ms",
+            ),
+        ] {
+            let (status, body) = post_json(&mut app, uri, body).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+            let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(error["details"]["field"], field, "{uri}");
+        }
+
+        for (uri, code, field) in [
+            ("/reports/abc", 400, None),
+            ("/test_steps/1", 404, None),
+            ("/reports/1/test_steps", 404, None),
+            ("/reports?verdict=PASSED", 400, Some("verdict")),
+            ("/reports?limit=-1", 400, Some("limit")),
+            ("/unknown", 404, None),
+        ] {
+            let (status, body) = send(&mut app, http::Method::GET, uri, Body::empty()).await;
+            assert_eq!(status.as_u16(), code, "{uri}");
+            let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(error["code"], code, "{uri}");
+            assert!(error["message"].is_string(), "{uri}");
+            assert_eq!(error["details"]["field"].as_str(), field, "{uri}");
+        }
+
+        let response = app
+            .ready()
+            .await
+            .unwrap()
+            .call(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri("/projects")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[http::header::ALLOW], "POST,GET,HEAD");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["code"], 405);
+    }
+
+    #[tokio::test]
+    async fn test_migrations_on_existing_data() {
+        let (db, _tmp_file) = connect_temp_database().await;
+        migrator::Migrator::up(&db, Some(2)).await.unwrap();
+        for verdict in ["passed", "SUCCESS", "Fail", "error", "whatever"] {
+            db.execute_unprepared(&format!(
+                "INSERT INTO report (date, project, name, verdict) \
+                 VALUES ('2014-11-28T21:00:09+09:00', 'TestProjekt', 'TestReport', '{verdict}')"
+            ))
+            .await
+            .unwrap();
+        }
+        for verdict in ["passed", "FAILED"] {
+            db.execute_unprepared(&format!(
+                "INSERT INTO test_step (name, step_number, date, verdict, report_id) \
+                 VALUES ('Bus Lesen', 1, '2014-11-28 12:00:09', '{verdict}', 1)"
+            ))
+            .await
+            .unwrap();
+        }
+        migrator::Migrator::up(&db, None).await.unwrap();
+
+        let reports = Report::find().all(&db).await.unwrap();
+        let projects = Project::find().all(&db).await.unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "TestProjekt");
+        assert!(reports.iter().all(|r| r.project_id == Some(projects[0].id)));
+        assert_eq!(reports[0].declared_verdict, Verdict::Success);
+        assert_eq!(reports[0].computed_verdict, Some(Verdict::Failed));
+        assert_eq!(reports[1].computed_verdict, None);
+        // Only SQLite kept the offsets, the dates are comparable afterwards
+        if db.get_database_backend() == DbBackend::Sqlite {
+            assert_eq!(reports[0].date_offset, 9 * 3600);
+            assert_eq!(
+                reports[0].clone().with_original_offset().date.to_rfc3339(),
+                "2014-11-28T21:00:09+09:00"
+            );
+            let steps = TestStep::find()
+                .filter(test_step::Column::Date.gte(date::to_utc(reports[0].date)))
+                .all(&db)
+                .await
+                .unwrap();
+            assert_eq!(steps.len(), 2);
+        }
+        let verdicts: Vec<_> = reports.into_iter().map(|r| r.verdict).collect();
+        assert_eq!(
+            verdicts,
+            [
+                Verdict::Success,
+                Verdict::Success,
+                Verdict::Failed,
+                Verdict::Error,
+                Verdict::None
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dates_keep_their_offset() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "TestProjekt").await;
+
+        post_json(
+            &mut app,
+            "/reports/bulk",
+            json!({"date": "2014-11-28T21:00:09+09:00",
+            "project": "TestProjekt",
+            "name": "TestReport",
+            "verdict": "SUCCESS",
+            "test_steps": [
+                {"name": "Bus Lesen",
+                "step_number": 1,
+                "date": "2014-11-28T07:00:10.250-05:00",
+                "verdict": "SUCCESS"}
+            ]}),
+        )
+        .await;
+        let (status, _) = post_json(
+            &mut app,
+            "/reports/1/test_steps",
+            json!({"name": "Mess Lesen",
+                "step_number": 2,
+                "date": "2014-11-28T12:00:11Z",
+                "verdict": "SUCCESS"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        assert_eq!(
+            get_json(&mut app, "/reports/1").await["date"],
+            "2014-11-28T21:00:09+09:00"
+        );
+        assert_eq!(
+            get_json(&mut app, "/reports?to=2014-11-28T12:00:09Z").await[0]["date"],
+            "2014-11-28T21:00:09+09:00"
+        );
+        let dates: Vec<_> = get_json(&mut app, "/reports/1/test_steps")
+            .await
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|step| step["date"].clone())
+            .collect();
+        assert_eq!(
+            dates,
+            ["2014-11-28T07:00:10.250-05:00", "2014-11-28T12:00:11+00:00"]
+        );
+
+        // Filters compare the instants, not the local times
+        let (status, body) = send(
+            &mut app,
+            http::Method::GET,
+            "/test_steps?from=2014-11-28T12:00:10%2B00:00&to=2014-11-28T12:00:10.5Z",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let steps: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(steps.as_array().unwrap().len(), 1);
+        assert_eq!(steps[0]["name"], "Bus Lesen");
+
+        let (_, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/test_steps/2",
+            Body::from(r#"{"date": "2014-11-28T13:30:11+01:30"}"#),
+        )
+        .await;
+        let step: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(step["date"], "2014-11-28T13:30:11+01:30");
+        assert_eq!(
+            get_json(&mut app, "/test_steps/2").await["date"],
+            "2014-11-28T13:30:11+01:30"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrations_down_and_up() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        migrator::Migrator::reset(&db).await.unwrap();
+        assert!(!SchemaManager::new(&db).has_table("report").await.unwrap());
+        migrator::Migrator::up(&db, None).await.unwrap();
+        assert_eq!(Report::find().all(&db).await.unwrap(), []);
+    }
+
+    async fn create_project(app: &mut Router, name: &str) {
+        let (status, _) = post_json(app, "/projects", json!({ "name": name })).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    async fn get_json(app: &mut Router, uri: &str) -> serde_json::Value {
+        let (status, body) = send(app, http::Method::GET, uri, Body::empty()).await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_computed_verdict() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "TestProjekt").await;
+
+        for derive_verdict in [false, true] {
+            post_json(
+                &mut app,
+                "/reports",
+                json!({"date": "2014-11-28T21:00:09+09:00",
+                    "project": "TestProjekt",
+                    "name": "TestReport",
+                    "verdict": "SUCCESS",
+                    "derive_verdict": derive_verdict}),
+            )
+            .await;
+        }
+
+        for report_id in [1, 2] {
+            let report = get_json(&mut app, &format!("/reports/{report_id}")).await;
+            assert_eq!(report["verdict"], "SUCCESS");
+            assert_eq!(report["computed_verdict"], serde_json::Value::Null);
+
+            for (step_number, verdict) in [(1, "SUCCESS"), (2, "FAILED"), (3, "SKIPPED")] {
+                let (status, _) = post_json(
+                    &mut app,
+                    &format!("/reports/{report_id}/test_steps"),
+                    json!({"name": "Mess Lesen",
+                        "step_number": step_number,
+                        "date": "2014-11-28T21:00:02+09:00",
+                        "verdict": verdict}),
+                )
+                .await;
+                assert_eq!(status, StatusCode::OK);
+            }
+        }
+
+        let report = get_json(&mut app, "/reports/1").await;
+        assert_eq!(report["verdict"], "SUCCESS");
+        assert_eq!(report["declared_verdict"], "SUCCESS");
+        assert_eq!(report["computed_verdict"], "FAILED");
+
+        let report = get_json(&mut app, "/reports/2").await;
+        assert_eq!(report["verdict"], "FAILED");
+        assert_eq!(report["declared_verdict"], "SUCCESS");
+        assert_eq!(report["computed_verdict"], "FAILED");
+    }
+
+    #[tokio::test]
+    async fn test_import_junit() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "TestProjekt").await;
+
+        let (status, body) = send(
+            &mut app,
+            http::Method::POST,
+            "/import/junit?project=TestProjekt",
+            Body::from(
+                r#"<testsuites>
+                    <testsuite name="first" timestamp="2014-11-28T12:00:09">
+                        <testcase name="Bus Lesen" time="1"/>
+                        <testcase name="Mess Lesen"><failure/></testcase>
+                        <system-out>Bench HIL1</system-out>
+                    </testsuite>
+                    <testsuite name="second" timestamp="2014-11-28T12:00:09">
+                        <testcase name="Kalib Lesen"/>
+                    </testsuite>
+                </testsuites>"#,
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "[1,2]");
+
+        let report = get_json(&mut app, "/reports/1").await;
+        assert_eq!(report["project"], "TestProjekt");
+        assert_eq!(report["name"], "first");
+        assert_eq!(report["verdict"], "FAILED");
+        assert_eq!(report["output"], "Bench HIL1");
+        let steps = get_json(&mut app, "/reports/1/test_steps").await;
+        assert_eq!(steps[0]["name"], "Bus Lesen");
+        assert_eq!(steps[0]["verdict"], "SUCCESS");
+        assert_eq!(steps[1]["step_number"], 2);
+        assert_eq!(steps[1]["date"], "2014-11-28T12:00:10+00:00");
+        assert_eq!(steps[1]["verdict"], "FAILED");
+        assert_eq!(get_json(&mut app, "/reports/2").await["verdict"], "SUCCESS");
+
+        let (status, _) = send(
+            &mut app,
+            http::Method::POST,
+            "/import/junit?project=TestProjekt",
+            Body::from("<testsuites>"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            get_json(&mut app, "/reports")
+                .await
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_atx() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "TestProjekt").await;
+
+        let (status, body) = send(
+            &mut app,
+            http::Method::POST,
+            "/import/atx?project=TestProjekt",
+            Body::from(
+                r#"<ATX><TEST-CASE>
+                    <SHORT-NAME>MyFancyTestCase</SHORT-NAME>
+                    <DATE>2014-11-28T12:00:09+00:00</DATE>
+                    <VERDICT>INCONCLUSIVE</VERDICT>
+                    <TEST-STEP><SHORT-NAME>Bus_Lesen</SHORT-NAME><VERDICT>PASSED</VERDICT></TEST-STEP>
+                </TEST-CASE></ATX>"#,
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "[1]");
+
+        let report = get_json(&mut app, "/reports/1").await;
+        assert_eq!(report["name"], "MyFancyTestCase");
+        assert_eq!(report["verdict"], "INCONCLUSIVE");
+        assert_eq!(report["computed_verdict"], "SUCCESS");
+        assert_eq!(report["derive_verdict"], false);
+        let steps = get_json(&mut app, "/reports/1/test_steps").await;
+        assert_eq!(steps[0]["name"], "Bus_Lesen");
+        assert_eq!(steps[0]["date"], "2014-11-28T12:00:09+00:00");
+    }
+
+    #[tokio::test]
+    async fn test_import_libtest() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "TestProjekt").await;
+
+        let (status, body) = send(
+            &mut app,
+            http::Method::POST,
+            "/import/libtest?project=TestProjekt&name=unit%20tests",
+            Body::from(
+                r#"{ "type": "suite", "event": "started", "test_count": 1 }
+                { "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.25 }
+                { "type": "suite", "event": "ok", "passed": 1, "failed": 0 }"#,
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "[1]");
+
+        let report = get_json(&mut app, "/reports/1").await;
+        assert_eq!(report["name"], "unit tests");
+        assert_eq!(report["verdict"], "SUCCESS");
+        let steps = get_json(&mut app, "/reports/1/test_steps").await;
+        assert_eq!(steps[0]["name"], "tests::a");
+        assert_eq!(steps[0]["duration_ms"], 250);
+    }
+
+    #[tokio::test]
+    async fn test_add_report_with_steps() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db.clone(), Features::default());
+        create_project(&mut app, "TestProjekt").await;
+
+        let (status, body) = post_json(
+            &mut app,
+            "/reports/bulk",
+            json!({"date": "2014-11-28T12:00:09+00:00",
+            "project": "TestProjekt",
+            "name": "TestReport",
+            "verdict": "SUCCESS",
+            "test_steps": [
+                {"name": "Bus Lesen",
+                "step_number": 1,
+                "date": "2014-11-28T12:00:10+00:00",
+                "verdict": "SUCCESS"},
+                {"name": "Mess Lesen",
+                "step_number": 2,
+                "date": "2014-11-28T12:00:11+00:00",
+                "verdict": "FAILED"}
+            ]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created["id"], 1);
+        assert_eq!(created["verdict"], "SUCCESS");
+        assert_eq!(created["computed_verdict"], "FAILED");
+        assert_eq!(created["test_steps"][0]["id"], 1);
+        assert_eq!(created["test_steps"][1]["id"], 2);
+        assert_eq!(created["test_steps"][1]["report_id"], 1);
+        assert_eq!(
+            get_json(&mut app, "/reports/1/test_steps").await,
+            created["test_steps"]
+        );
+
+        // Nothing gets stored if any of the steps is invalid
+        let (status, _) = post_json(
+            &mut app,
+            "/reports/bulk",
+            json!({"date": "2014-11-28T12:00:09+00:00",
+            "project": "TestProjekt",
+            "name": "TestReport",
+            "verdict": "SUCCESS",
+            "test_steps": [
+                {"name": "Bus Lesen",
+                "step_number": 1,
+                "date": "2014-11-28T12:00:10+00:00",
+                "verdict": "SUCCESS"},
+                {"name": "Mess Lesen",
+                "step_number": 2,
+                "date": "2014-11-28T12:00:11+00:00",
+                "verdict": "PASSED"}
+            ]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            get_json(&mut app, "/reports")
+                .await
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Neither if the database fails after the report and the first step
+        // were inserted
+        db.execute_unprepared("DROP TABLE measurement")
+            .await
+            .unwrap();
+        let (status, body) = post_json(
+            &mut app,
+            "/reports/bulk",
+            json!({"date": "2014-11-28T12:00:09+00:00",
+            "project": "TestProjekt",
+            "name": "TestReport",
+            "verdict": "SUCCESS",
+            "test_steps": [
+                {"name": "Bus Lesen",
+                "step_number": 1,
+                "date": "2014-11-28T12:00:10+00:00",
+                "verdict": "SUCCESS"},
+                {"name": "Mess Lesen",
+                "step_number": 2,
+                "date": "2014-11-28T12:00:11+00:00",
+                "verdict": "SUCCESS",
+                "measurements": [{"name": "Voltage", "value": 12.1}]}
+            ]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        // Details about the database are only logged
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["message"], "Internal Server Error");
+        assert_eq!(
+            get_json(&mut app, "/reports")
+                .await
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            get_json(&mut app, "/test_steps")
+                .await
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nested_test_steps() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "TestProjekt").await;
+
+        let (status, body) = post_json(
+            &mut app,
+            "/reports/bulk",
+            json!({"date": "2014-11-28T12:00:09+00:00",
+            "project": "TestProjekt",
+            "name": "TestReport",
+            "verdict": "SUCCESS",
+            "test_steps": [
+                {"name": "Precondition",
+                "step_number": 1,
+                "date": "2014-11-28T12:00:10+00:00",
+                "verdict": "SUCCESS",
+                "children": [
+                    {"name": "Bus Lesen",
+                    "step_number": 1,
+                    "date": "2014-11-28T12:00:10+00:00",
+                    "verdict": "FAILED"}
+                ]},
+                {"name": "Mess Lesen",
+                "step_number": 2,
+                "date": "2014-11-28T12:00:11+00:00",
+                "verdict": "SUCCESS"}
+            ]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created["computed_verdict"], "FAILED");
+        assert_eq!(created["test_steps"][1]["name"], "Bus Lesen");
+        assert_eq!(created["test_steps"][1]["parent_id"], 1);
+        assert_eq!(created["test_steps"][2]["parent_id"], json!(null));
+
+        // Sub-steps can also be added to existing steps of the same report
+        let (status, _) = post_json(
+            &mut app,
+            "/reports/1/test_steps",
+            json!({"name": "Kalib Lesen",
+            "step_number": 2,
+            "date": "2014-11-28T12:00:12+00:00",
+            "verdict": "ERROR",
+            "parent_id": 1}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = post_json(
+            &mut app,
+            "/reports/1/test_steps",
+            json!({"name": "Kalib Lesen",
+            "step_number": 2,
+            "date": "2014-11-28T12:00:12+00:00",
+            "verdict": "ERROR",
+            "parent_id": 42}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["details"]["field"], "parent_id");
+
+        let tree = get_json(&mut app, "/reports/1/test_steps/tree").await;
+        assert_eq!(tree[0]["name"], "Precondition");
+        assert_eq!(tree[0]["verdict"], "SUCCESS");
+        assert_eq!(tree[0]["computed_verdict"], "ERROR");
+        assert_eq!(tree[0]["children"][0]["name"], "Bus Lesen");
+        assert_eq!(tree[0]["children"][1]["name"], "Kalib Lesen");
+        assert_eq!(tree[1]["name"], "Mess Lesen");
+        assert_eq!(tree[1]["children"], json!([]));
+
+        // Deleting a step deletes its sub-steps as well
+        let (status, _) = send(
+            &mut app,
+            http::Method::DELETE,
+            "/test_steps/1",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let steps = get_json(&mut app, "/reports/1/test_steps").await;
+        assert_eq!(steps.as_array().unwrap().len(), 1);
+        assert_eq!(
+            get_json(&mut app, "/reports/1").await["computed_verdict"],
+            "SUCCESS"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failure_details() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "TestProjekt").await;
+
+        let (status, body) = post_json(
+            &mut app,
+            "/reports/bulk",
+            json!({"date": "2014-11-28T12:00:09+00:00",
+            "project": "TestProjekt",
+            "name": "TestReport",
+            "verdict": "FAILED",
+            "test_steps": [
+                {"name": "Mess Lesen",
+                "step_number": 1,
+                "date": "2014-11-28T12:00:10+00:00",
+                "verdict": "FAILED",
+                "message": "Voltage out of range",
+                "expected": "12.0",
+                "actual": "11.2",
+                "unit": "V",
+                "failure_category": "ASSERTION"},
+                {"name": "Bus Lesen",
+                "step_number": 2,
+                "date": "2014-11-28T12:00:11+00:00",
+                "verdict": "ERROR",
+                "message": "No response from ECU",
+                "failure_category": "TIMEOUT"}
+            ]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+
+        let step = get_json(&mut app, "/test_steps/1").await;
+        assert_eq!(step["message"], "Voltage out of range");
+        assert_eq!(step["expected"], "12.0");
+        assert_eq!(step["actual"], "11.2");
+        assert_eq!(step["unit"], "V");
+        assert_eq!(step["failure_category"], "ASSERTION");
+
+        let names = |steps: serde_json::Value| {
+            steps
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|step| step["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(get_json(&mut app, "/test_steps?message=range").await),
+            ["Mess Lesen"]
+        );
+        assert_eq!(
+            names(get_json(&mut app, "/test_steps?failure_category=TIMEOUT").await),
+            ["Bus Lesen"]
+        );
+
+        let (status, _) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/test_steps/2",
+            Body::from(json!({"failure_category": "ENVIRONMENT"}).to_string()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            names(get_json(&mut app, "/test_steps?failure_category=ENVIRONMENT").await),
+            ["Bus Lesen"]
+        );
+
+        let (status, body) = send(
+            &mut app,
+            http::Method::GET,
+            "/test_steps?failure_category=BROKEN",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["details"]["field"], "failure_category");
+    }
+
+    /// Sends a `multipart/form-data` request with the given fields, fields
+    /// with a filename are sent as files.
+    async fn post_multipart(
+        app: &mut Router,
+        uri: &str,
+        fields: &[(&str, Option<&str>, &str)],
+    ) -> (StatusCode, Bytes) {
+        const BOUNDARY: &str = "result-guide-boundary";
+        let mut body = String::new();
+        for (name, filename, content) in fields {
+            body.push_str(&format!("--{BOUNDARY}\r\n"));
+            match filename {
+                Some(filename) => body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{name}\"; filename=\"{filename}\"\r\nContent-Type: text/plain\r\n"
+                )),
+                None => body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{name}\"\r\n"
+                )),
+            }
+            body.push_str(&format!("\r\n{content}\r\n"));
+        }
+        body.push_str(&format!("--{BOUNDARY}--\r\n"));
+        let response = app
+            .ready()
+            .await
+            .unwrap()
+            .call(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(uri)
+                    .header(
+                        http::header::CONTENT_TYPE,
+                        format!("multipart/form-data; boundary={BOUNDARY}"),
+                    )
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, body)
+    }
+
+    #[tokio::test]
+    async fn test_attachments() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let blobs = Arc::new(MemoryBlobStore::default());
+        let mut app = app(db, Features::default(), blobs.clone(), 1024);
+        create_project(&mut app, "TestProjekt").await;
+        let (status, _) = post_json(
+            &mut app,
+            "/reports/bulk",
+            json!({"date": "2014-11-28T12:00:09+00:00",
+            "project": "TestProjekt",
+            "name": "TestReport",
+            "verdict": "SUCCESS",
+            "test_steps": [
+                {"name": "Bus Lesen",
+                "step_number": 1,
+                "date": "2014-11-28T12:00:10+00:00",
+                "verdict": "SUCCESS"}
+            ]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = post_multipart(
+            &mut app,
+            "/reports/1/attachments",
+            &[
+                ("file", Some("test.log"), "hello"),
+                ("file", Some("bus.asc"), "0x42"),
+            ],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            created[0],
+            json!({"id": 1,
+                "report_id": 1,
+                "test_step_id": null,
+                "filename": "test.log",
+                "mime_type": "text/plain",
+                "size": 5,
+                "sha256": "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"})
+        );
+        assert_eq!(created[1]["filename"], "bus.asc");
+
+        // The same content is only stored once
+        let (status, _) = post_multipart(
+            &mut app,
+            "/reports/1/attachments",
+            &[
+                ("test_step_id", None, "1"),
+                ("file", Some("step.log"), "hello"),
+            ],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(blobs.len(), 2);
+
+        let (status, body) = post_multipart(
+            &mut app,
+            "/reports/1/attachments",
+            &[
+                ("test_step_id", None, "42"),
+                ("file", Some("step.log"), "hello"),
+            ],
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["details"]["field"], "test_step_id");
+        let (status, _) =
+            post_multipart(&mut app, "/reports/1/attachments", &[("name", None, "x")]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = post_multipart(
+            &mut app,
+            "/reports/1/attachments",
+            &[("file", Some("big.log"), &"x".repeat(2048))],
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        let (status, body) = post_json(&mut app, "/reports/1/attachments", json!({})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(error["message"]
+            .as_str()
+            .unwrap()
+            .contains("multipart/form-data"));
+
+        let attachments = get_json(&mut app, "/reports/1/attachments").await;
+        assert_eq!(attachments.as_array().unwrap().len(), 3);
+        let attachments = get_json(&mut app, "/test_steps/1/attachments").await;
+        assert_eq!(attachments[0]["filename"], "step.log");
+        assert_eq!(get_json(&mut app, "/attachments/2").await, created[1]);
+
+        let response = app
+            .ready()
+            .await
+            .unwrap()
+            .call(
+                Request::builder()
+                    .uri("/attachments/1/content")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[http::header::CONTENT_TYPE], "text/plain");
+        assert_eq!(
+            response.headers()[http::header::CONTENT_DISPOSITION],
+            "attachment; filename=\"test.log\""
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, "hello");
+
+        // Blobs are only deleted once no attachment uses them anymore
+        let (status, _) = send(
+            &mut app,
+            http::Method::DELETE,
+            "/test_steps/1",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(blobs.len(), 2);
+        let (status, _) = send(&mut app, http::Method::DELETE, "/reports/1", Body::empty()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(blobs.len(), 0);
+        let (status, _) = send(&mut app, http::Method::GET, "/attachments/1", Body::empty()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_measurements() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "A").await;
+        create_project(&mut app, "B").await;
+
+        for (project, date) in [
+            ("A", "2014-11-28T12:00:00+00:00"),
+            ("B", "2014-11-29T12:00:00+00:00"),
+        ] {
+            let (status, body) = post_json(
+                &mut app,
+                "/reports/bulk",
+                json!({"date": date,
+                "project": project,
+                "name": "TestReport",
+                "verdict": "SUCCESS",
+                "test_steps": [
+                    {"name": "Mess Lesen",
+                    "step_number": 1,
+                    "date": date,
+                    "verdict": "SUCCESS",
+                    "measurements": [
+                        {"name": "Voltage", "value": 12.1, "unit": "V", "lower_limit": 11.5, "upper_limit": 12.5},
+                        {"name": "Current", "value": 0.5, "unit": "A"}
+                    ]}
+                ]}),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        }
+
+        let (status, body) = post_json(
+            &mut app,
+            "/test_steps/1/measurements",
+            json!([
+                {"name": "Voltage", "value": 11.2, "unit": "V", "lower_limit": 11.5, "upper_limit": 12.5},
+                {"name": "Temperature", "value": 80.0, "passed": false}
+            ]),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        let step_measurements: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(step_measurements.as_array().unwrap().len(), 4);
+        assert_eq!(
+            step_measurements[0],
+            json!({"id": 1,
+                "test_step_id": 1,
+                "name": "Voltage",
+                "value": 12.1,
+                "unit": "V",
+                "lower_limit": 11.5,
+                "upper_limit": 12.5,
+                "passed": true})
+        );
+        assert_eq!(step_measurements[2]["passed"], false);
+        assert_eq!(step_measurements[3]["passed"], false);
+        assert_eq!(
+            get_json(&mut app, "/test_steps/1/measurements").await,
+            step_measurements
+        );
+
+        let voltages = get_json(&mut app, "/measurements?name=Voltage&order=desc").await;
+        let summary: Vec<_> = voltages
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| {
+                (
+                    m["report_id"].as_i64().unwrap(),
+                    m["value"].as_f64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(summary, [(2, 12.1), (1, 11.2), (1, 12.1)]);
+        assert_eq!(voltages[0]["date"], "2014-11-29T12:00:00+00:00");
+        assert_eq!(
+            get_json(&mut app, "/measurements?name=Voltage&project=B")
+                .await
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+        let (status, _) = send(&mut app, http::Method::GET, "/measurements", Body::empty()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Measurements are deleted together with their step
+        let (status, _) = send(&mut app, http::Method::DELETE, "/reports/1", Body::empty()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            get_json(&mut app, "/measurements?name=Voltage")
+                .await
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_report_metadata() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "A").await;
+
+        let (status, body) = post_json(
+            &mut app,
+            "/reports",
+            json!({"date": "2014-11-28T12:00:00Z",
+                "project": "A",
+                "name": "First",
+                "verdict": "SUCCESS",
+                "properties": {"sw_version": "1.2.3", "bench": "HIL1"},
+                "tags": ["nightly", "smoke", "nightly"]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        let (status, body) = post_json(
+            &mut app,
+            "/reports/bulk",
+            json!({"date": "2014-11-29T12:00:00Z",
+                "project": "A",
+                "name": "Second",
+                "verdict": "FAILED",
+                "properties": {"sw_version": "1.2.4"},
+                "tags": ["nightly"],
+                "test_steps": []}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created["properties"], json!({"sw_version": "1.2.4"}));
+        assert_eq!(created["tags"], json!(["nightly"]));
+
+        let report = get_json(&mut app, "/reports/1").await;
+        assert_eq!(
+            report["properties"],
+            json!({"bench": "HIL1", "sw_version": "1.2.3"})
+        );
+        assert_eq!(report["tags"], json!(["nightly", "smoke"]));
+
+        async fn ids(app: &mut Router, uri: &str) -> Vec<i64> {
+            get_json(app, uri)
+                .await
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|report| report["id"].as_i64().unwrap())
+                .collect()
+        }
+        assert_eq!(ids(&mut app, "/reports?tag=nightly").await, vec![1, 2]);
+        assert_eq!(ids(&mut app, "/reports?tag=smoke").await, vec![1]);
+        assert_eq!(
+            ids(&mut app, "/reports?prop.sw_version=1.2.3").await,
+            vec![1]
+        );
+        assert_eq!(
+            ids(&mut app, "/reports?tag=nightly&prop.sw_version=1.2.4").await,
+            vec![2]
+        );
+        assert_eq!(
+            ids(&mut app, "/reports?prop.sw_version=1.2.4&prop.bench=HIL1").await,
+            Vec::<i64>::new()
+        );
+        assert_eq!(
+            ids(&mut app, "/projects/1/reports?tag=smoke").await,
+            vec![1]
+        );
+
+        // PATCH merges properties, null removes one, tags are replaced
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/reports/1",
+            Body::from(
+                json!({"properties": {"bench": null, "sw_version": "1.3.0"}, "tags": ["weekly"]})
+                    .to_string(),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["properties"], json!({"sw_version": "1.3.0"}));
+        assert_eq!(report["tags"], json!(["weekly"]));
+
+        // PATCH without metadata keeps it
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/reports/1",
+            Body::from(json!({"name": "Renamed"}).to_string()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["tags"], json!(["weekly"]));
+
+        // PUT replaces everything
+        let (status, body) = send(
+            &mut app,
+            http::Method::PUT,
+            "/reports/1",
+            Body::from(
+                json!({"date": "2014-11-28T12:00:00Z",
+                    "project": "A",
+                    "name": "First",
+                    "verdict": "SUCCESS",
+                    "properties": {"bench": "HIL2"}})
+                .to_string(),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["properties"], json!({"bench": "HIL2"}));
+        assert_eq!(report["tags"], json!([]));
+
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/reports/1",
+            Body::from(json!({"tags": [" "]}).to_string()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["details"]["field"], "tags");
+
+        let (status, _) = send(&mut app, http::Method::DELETE, "/reports/2", Body::empty()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            ids(&mut app, "/reports?tag=nightly").await,
+            Vec::<i64>::new()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_test_runs() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "A").await;
+        create_project(&mut app, "B").await;
+
+        let (status, body) = post_json(
+            &mut app,
+            "/test_runs",
+            json!({"project": "A",
+                "name": "Nightly 2014-11-28",
+                "start": "2014-11-28T21:00:00+09:00",
+                "trigger": "nightly"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        assert_eq!(body, "1");
+
+        for (name, verdict) in [
+            ("First", "SUCCESS"),
+            ("Second", "FAILED"),
+            ("Third", "SUCCESS"),
+        ] {
+            let (status, body) = post_json(
+                &mut app,
+                "/reports",
+                json!({"date": "2014-11-28T21:00:09+09:00",
+                    "project": "A",
+                    "name": name,
+                    "verdict": verdict,
+                    "test_run_id": 1}),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        }
+        let (status, _) = post_json(
+            &mut app,
+            "/reports",
+            json!({"date": "2014-11-28T21:00:09+09:00",
+                "project": "A",
+                "name": "Outside",
+                "verdict": "ERROR"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Runs only take reports of their own project
+        let (status, body) = post_json(
+            &mut app,
+            "/reports",
+            json!({"date": "2014-11-28T21:00:09+09:00",
+                "project": "B",
+                "name": "Other",
+                "verdict": "SUCCESS",
+                "test_run_id": 1}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["details"]["field"], "test_run_id");
+
+        assert_eq!(
+            get_json(&mut app, "/test_runs/1").await,
+            json!({"id": 1,
+                "project_id": 1,
+                "name": "Nightly 2014-11-28",
+                "start": "2014-11-28T21:00:00+09:00",
+                "end": null,
+                "trigger": "nightly",
+                "report_count": 3,
+                "verdicts": {"SUCCESS": 2, "FAILED": 1},
+                "passed": 2,
+                "failed": 1,
+                "verdict": "FAILED"})
+        );
+
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/reports/4",
+            Body::from(json!({"test_run_id": 1}).to_string()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/test_runs/1",
+            Body::from(json!({"end": "2014-11-29T06:00:00+09:00"}).to_string()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        let run: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(run["end"], "2014-11-29T06:00:00+09:00");
+        assert_eq!(run["report_count"], 4);
+        assert_eq!(run["failed"], 2);
+        assert_eq!(run["verdict"], "ERROR");
+
+        let reports = get_json(&mut app, "/test_runs/1/reports?verdict=SUCCESS").await;
+        assert_eq!(reports.as_array().unwrap().len(), 2);
+        let reports = get_json(&mut app, "/reports?test_run_id=1").await;
+        assert_eq!(reports.as_array().unwrap().len(), 4);
+        let runs = get_json(&mut app, "/test_runs?project=A&trigger=nightly").await;
+        assert_eq!(runs.as_array().unwrap().len(), 1);
+        let runs = get_json(&mut app, "/test_runs?project=B").await;
+        assert_eq!(runs, json!([]));
+
+        // `null` clears the end and trigger, e.g. when a run is resumed
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/test_runs/1",
+            Body::from(json!({"end": null, "trigger": null}).to_string()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        let run: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(run["end"], serde_json::Value::Null);
+        assert_eq!(run["trigger"], serde_json::Value::Null);
+        assert_eq!(
+            get_json(&mut app, "/test_runs/1").await["end"],
+            serde_json::Value::Null
+        );
+        let runs = get_json(&mut app, "/test_runs?project=A&trigger=nightly").await;
+        assert_eq!(runs, json!([]));
+
+        let (status, _) = send(&mut app, http::Method::DELETE, "/projects/1", Body::empty()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        // Deleting a run keeps its reports
+        let (status, _) = send(
+            &mut app,
+            http::Method::DELETE,
+            "/test_runs/1",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&mut app, http::Method::GET, "/test_runs/1", Body::empty()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let report = get_json(&mut app, "/reports/1").await;
+        assert_eq!(report["test_run_id"], json!(null));
+    }
+
+    #[tokio::test]
+    async fn test_history() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "A").await;
+
+        for (name, date, verdict, step_verdict) in [
+            (
+                "Case.pkg",
+                "2014-11-28T12:00:00+01:00",
+                "SUCCESS",
+                "SUCCESS",
+            ),
+            ("Case.pkg", "2014-11-29T12:00:00+01:00", "FAILED", "FAILED"),
+            (
+                "Other.pkg",
+                "2014-11-29T13:00:00+01:00",
+                "SUCCESS",
+                "SUCCESS",
+            ),
+            ("Case.pkg", "2014-11-30T12:00:00+01:00", "SUCCESS", "FAILED"),
+            ("Case.pkg", "2014-12-01T12:00:00+01:00", "FAILED", "SUCCESS"),
+            ("Case.pkg", "2014-12-02T12:00:00+01:00", "ERROR", "SUCCESS"),
+        ] {
+            let (status, body) = post_json(
+                &mut app,
+                "/reports/bulk",
+                json!({"date": date,
+                    "project": "A",
+                    "name": name,
+                    "verdict": verdict,
+                    "test_steps": [{"name": "Bus Lesen",
+                        "step_number": 1,
+                        "date": date,
+                        "verdict": step_verdict}]}),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        }
+
+        let history = get_json(&mut app, "/projects/1/test_cases/Case.pkg/history").await;
+        let verdicts: Vec<_> = history["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["verdict"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            verdicts,
+            ["SUCCESS", "FAILED", "SUCCESS", "FAILED", "ERROR"]
+        );
+        assert_eq!(history["total"], 5);
+        assert_eq!(
+            history["verdicts"],
+            json!({"SUCCESS": 2, "FAILED": 2, "ERROR": 1})
+        );
+        assert_eq!(
+            history["last_success"],
+            json!({"report_id": 4,
+                "test_step_id": null,
+                "date": "2014-11-30T12:00:00+01:00",
+                "verdict": "SUCCESS"})
+        );
+        assert_eq!(history["first_failure"]["report_id"], 5);
+        assert_eq!(history["window"], 20);
+        assert_eq!(history["pass_rate"], 0.4);
+
+        let history = get_json(
+            &mut app,
+            "/projects/1/test_cases/Case.pkg/history?window=3&from=2014-11-29T00:00:00Z",
+        )
+        .await;
+        assert_eq!(history["total"], 4);
+        assert_eq!(history["pass_rate"], json!(1.0 / 3.0));
+
+        // Following a step, which has been passing since the last failure
+        let history = get_json(
+            &mut app,
+            "/projects/1/test_cases/Case.pkg/history?step=Bus%20Lesen",
+        )
+        .await;
+        assert_eq!(history["step"], "Bus Lesen");
+        assert_eq!(history["entries"][0]["test_step_id"], 1);
+        assert_eq!(history["last_success"]["test_step_id"], 6);
+        assert_eq!(history["first_failure"], json!(null));
+        assert_eq!(history["pass_rate"], 0.6);
+
+        let history = get_json(&mut app, "/projects/1/test_cases/Unknown/history").await;
+        assert_eq!(history["total"], 0);
+        assert_eq!(history["entries"], json!([]));
+        assert_eq!(history["pass_rate"], json!(null));
+
+        let (status, _) = send(
+            &mut app,
+            http::Method::GET,
+            "/projects/2/test_cases/Case.pkg/history",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_flaky() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "A").await;
+
+        for (day, verdicts) in [
+            (1, ["SUCCESS", "SUCCESS", "SUCCESS"]),
+            (2, ["FAILED", "SUCCESS", "SUCCESS"]),
+            (3, ["SUCCESS", "SUCCESS", "FAILED"]),
+            (4, ["ERROR", "SUCCESS", "FAILED"]),
+            (5, ["SUCCESS", "SKIPPED", "FAILED"]),
+        ] {
+            for (name, verdict) in ["Flaky.pkg", "Stable.pkg", "Broken.pkg"]
+                .into_iter()
+                .zip(verdicts)
+            {
+                let date = format!("2014-11-{day:02}T12:00:00Z");
+                let (status, body) = post_json(
+                    &mut app,
+                    "/reports/bulk",
+                    json!({"date": date,
+                        "project": "A",
+                        "name": name,
+                        "verdict": verdict,
+                        "test_steps": [{"name": "Bus Lesen",
+                            "step_number": 1,
+                            "date": date,
+                            "verdict": verdict}]}),
+                )
+                .await;
+                assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+            }
+        }
+
+        let flaky = get_json(&mut app, "/projects/1/flaky").await;
+        assert_eq!(
+            flaky,
+            json!([
+                {"name": "Flaky.pkg",
+                    "step": null,
+                    "executions": 5,
+                    "passed": 3,
+                    "failed": 2,
+                    "flips": 4,
+                    "flip_rate": 1.0,
+                    "last_verdict": "SUCCESS"},
+                {"name": "Broken.pkg",
+                    "step": null,
+                    "executions": 5,
+                    "passed": 2,
+                    "failed": 3,
+                    "flips": 1,
+                    "flip_rate": 0.25,
+                    "last_verdict": "FAILED"}
+            ])
+        );
+
+        // Only the last executions count
+        let flaky = get_json(&mut app, "/projects/1/flaky?window=3").await;
+        assert_eq!(flaky.as_array().unwrap().len(), 1);
+        assert_eq!(flaky[0]["name"], "Flaky.pkg");
+        let flaky = get_json(&mut app, "/projects/1/flaky?min_flip_rate=0.5").await;
+        assert_eq!(flaky.as_array().unwrap().len(), 1);
+
+        let flaky = get_json(&mut app, "/projects/1/flaky?steps=true&min_flip_rate=0.5").await;
+        assert_eq!(flaky[0]["name"], "Flaky.pkg");
+        assert_eq!(flaky[0]["step"], "Bus Lesen");
+        assert_eq!(flaky[0]["flips"], 4);
+
+        let (status, _) = send(
+            &mut app,
+            http::Method::GET,
+            "/projects/1/flaky?window=1",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_baselines() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "A").await;
+
+        async fn upload(
+            app: &mut Router,
+            name: &str,
+            day: u32,
+            test_run_id: Option<i64>,
+            steps: &[(&str, &str)],
+        ) -> serde_json::Value {
+            let date = format!("2014-11-{day:02}T12:00:00Z");
+            let test_steps: Vec<_> = steps
+                .iter()
+                .enumerate()
+                .map(|(i, (name, verdict))| {
+                    json!({"name": name, "step_number": i + 1, "date": date, "verdict": verdict})
+                })
+                .collect();
+            let (status, body) = post_json(
+                app,
+                "/reports/bulk",
+                json!({"date": date,
+                    "project": "A",
+                    "name": name,
+                    "verdict": "SUCCESS",
+                    "derive_verdict": true,
+                    "test_run_id": test_run_id,
+                    "test_steps": test_steps}),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+            serde_json::from_slice(&body).unwrap()
+        }
+
+        let (status, _) = post_json(
+            &mut app,
+            "/test_runs",
+            json!({"project": "A", "name": "Release", "start": "2014-11-01T12:00:00Z"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let known = upload(
+            &mut app,
+            "Known.pkg",
+            1,
+            Some(1),
+            &[("Init", "SUCCESS"), ("Read", "FAILED")],
+        )
+        .await;
+        assert_eq!(known["baseline_status"], serde_json::Value::Null);
+        upload(&mut app, "Ok.pkg", 1, Some(1), &[("Init", "SUCCESS")]).await;
+
+        for (body, field) in [
+            (json!({}), None),
+            (json!({"report_id": 1, "test_run_id": 1}), None),
+            (json!({"report_id": 99}), Some("report_id")),
+            (json!({"test_run_id": 99}), Some("test_run_id")),
+        ] {
+            let (status, body) = send(
+                &mut app,
+                http::Method::PUT,
+                "/projects/1/baseline",
+                Body::from(body.to_string()),
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["details"]["field"].as_str(), field);
+        }
+        let (status, body) = send(
+            &mut app,
+            http::Method::PUT,
+            "/projects/1/baseline",
+            Body::from(r#"{"test_run_id": 1}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let project: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(project["baseline_test_run_id"], 1);
+        assert_eq!(project["baseline_report_id"], serde_json::Value::Null);
+
+        let known = upload(
+            &mut app,
+            "Known.pkg",
+            2,
+            None,
+            &[("Init", "SUCCESS"), ("Read", "FAILED")],
+        )
+        .await;
+        assert_eq!(known["baseline_status"], "KNOWN_FAILURE");
+        assert_eq!(known["test_steps"][0]["baseline_status"], "PASSED");
+        assert_eq!(known["test_steps"][1]["baseline_status"], "KNOWN_FAILURE");
+        let broken = upload(&mut app, "Ok.pkg", 2, None, &[("Init", "ERROR")]).await;
+        assert_eq!(broken["baseline_status"], "NEW_FAILURE");
+        assert_eq!(broken["test_steps"][0]["baseline_status"], "NEW_FAILURE");
+
+        // Not part of the baseline, the failing step is uploaded separately
+        let (status, body) = post_json(
+            &mut app,
+            "/reports",
+            json!({"date": "2014-11-02T12:00:00Z",
+                "project": "A",
+                "name": "New.pkg",
+                "verdict": "SUCCESS",
+                "derive_verdict": true}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let new_id = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            get_json(&mut app, &format!("/reports/{new_id}")).await["baseline_status"],
+            "PASSED"
+        );
+        let (status, _) = post_json(
+            &mut app,
+            &format!("/reports/{new_id}/test_steps"),
+            json!({"name": "Write",
+                "step_number": 1,
+                "date": "2014-11-02T12:00:00Z",
+                "verdict": "FAILED"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            get_json(&mut app, &format!("/reports/{new_id}")).await["baseline_status"],
+            "NEW_FAILURE"
+        );
+        // Changing a step compares the report again
+        let step_id =
+            get_json(&mut app, &format!("/reports/{new_id}/test_steps")).await[0]["id"].clone();
+        for (verdict, baseline_status) in [("SUCCESS", "PASSED"), ("FAILED", "NEW_FAILURE")] {
+            let (status, _) = send(
+                &mut app,
+                http::Method::PATCH,
+                &format!("/test_steps/{step_id}"),
+                Body::from(json!({ "verdict": verdict }).to_string()),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(
+                get_json(&mut app, &format!("/reports/{new_id}")).await["baseline_status"],
+                baseline_status
+            );
+        }
+
+        let regressions = get_json(&mut app, "/projects/1/regressions").await;
+        let summary: Vec<_> = regressions
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|regression| {
+                (
+                    regression["name"].as_str().unwrap(),
+                    regression["new_failures"][0]["name"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(summary, [("New.pkg", "Write"), ("Ok.pkg", "Init")]);
+        assert_eq!(regressions[1]["id"], broken["id"]);
+        let regressions = get_json(&mut app, "/projects/1/regressions?test_run_id=1").await;
+        assert_eq!(regressions, json!([]));
+
+        // Fixed again, reports of the baseline itself are not compared
+        let fixed = upload(&mut app, "Ok.pkg", 3, None, &[("Init", "SUCCESS")]).await;
+        assert_eq!(fixed["baseline_status"], "PASSED");
+        let regressions = get_json(&mut app, "/projects/1/regressions").await;
+        assert_eq!(regressions.as_array().unwrap().len(), 1);
+        let late = upload(&mut app, "Ok.pkg", 4, Some(1), &[("Init", "FAILED")]).await;
+        assert_eq!(late["baseline_status"], serde_json::Value::Null);
+
+        // A single report as baseline only applies to reports of its name
+        let (status, _) = send(
+            &mut app,
+            http::Method::PUT,
+            "/projects/1/baseline",
+            Body::from(json!({"report_id": broken["id"]}).to_string()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let broken_again = upload(&mut app, "Ok.pkg", 5, None, &[("Init", "FAILED")]).await;
+        assert_eq!(broken_again["baseline_status"], "KNOWN_FAILURE");
+        let known = upload(&mut app, "Known.pkg", 5, None, &[("Read", "FAILED")]).await;
+        assert_eq!(known["baseline_status"], "NEW_FAILURE");
+        // So does deleting one
+        let step_id = get_json(&mut app, &format!("/reports/{}/test_steps", known["id"])).await[0]
+            ["id"]
+            .clone();
+        let (status, _) = send(
+            &mut app,
+            http::Method::DELETE,
+            &format!("/test_steps/{step_id}"),
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            get_json(&mut app, &format!("/reports/{}", known["id"])).await["baseline_status"],
+            "PASSED"
+        );
+
+        let (status, _) = send(
+            &mut app,
+            http::Method::DELETE,
+            &format!("/reports/{}", broken["id"]),
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            get_json(&mut app, "/projects/1").await["baseline_report_id"],
+            serde_json::Value::Null
+        );
+        let unrelated = upload(&mut app, "Ok.pkg", 6, None, &[("Init", "FAILED")]).await;
+        assert_eq!(unrelated["baseline_status"], serde_json::Value::Null);
+
+        let (status, _) = send(
+            &mut app,
+            http::Method::DELETE,
+            "/projects/1/baseline",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Deleting the baseline run removes the baseline of the project
+        let (status, _) = send(
+            &mut app,
+            http::Method::PUT,
+            "/projects/1/baseline",
+            Body::from(r#"{"test_run_id": 1}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(
+            &mut app,
+            http::Method::DELETE,
+            "/test_runs/1",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        assert_eq!(
+            get_json(&mut app, "/projects/1").await["baseline_test_run_id"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[tokio::test]
+    async fn test_triage() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "A").await;
+
+        for (name, verdict, steps) in [
+            (
+                "Case.pkg",
+                "FAILED",
+                json!([["Init", "SUCCESS"], ["Read", "FAILED"]]),
+            ),
+            ("Other.pkg", "ERROR", json!([["Write", "SUCCESS"]])),
+            ("Ok.pkg", "SUCCESS", json!([])),
+        ] {
+            let test_steps: Vec<_> = steps
+                .as_array()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .map(|(i, step)| {
+                    json!({"name": step[0],
+                        "step_number": i + 1,
+                        "date": "2014-11-28T12:00:00Z",
+                        "verdict": step[1]})
+                })
+                .collect();
+            let (status, body) = post_json(
+                &mut app,
+                "/reports/bulk",
+                json!({"date": "2014-11-28T12:00:00Z",
+                    "project": "A",
+                    "name": name,
+                    "verdict": verdict,
+                    "test_steps": test_steps}),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        }
+
+        async fn ids(app: &mut Router, uri: &str) -> Vec<i64> {
+            get_json(app, uri)
+                .await
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|item| item["id"].as_i64().unwrap())
+                .collect()
+        }
+        assert_eq!(ids(&mut app, "/reports?untriaged=true").await, vec![1, 2]);
+
+        // The failing step of the first report
+        let (status, body) = post_json(
+            &mut app,
+            "/reports/1/triage",
+            json!({"test_step_id": 2,
+                "status": "OPEN",
+                "category": "REAL_DEFECT",
+                "ticket": "PROJ-123",
+                "comment": "Bus times out",
+                "author": "qa",
+                "date": "2014-11-29T10:00:00+01:00"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        assert_eq!(body, "1");
+
+        for (uri, body, field) in [
+            (
+                "/reports/1/triage",
+                json!({"status": "OPEN", "category": "KNOWN_ISSUE", "author": " "}),
+                Some("author"),
+            ),
+            (
+                "/reports/1/triage",
+                json!({"test_step_id": 3,
+                    "status": "OPEN",
+                    "category": "KNOWN_ISSUE",
+                    "author": "qa"}),
+                Some("test_step_id"),
+            ),
+            (
+                "/reports/1/triage",
+                json!({"status": "OPEN", "category": "FLAKY", "author": "qa"}),
+                Some("category"),
+            ),
+        ] {
+            let (status, body) = post_json(&mut app, uri, body).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["details"]["field"].as_str(), field);
+        }
+        let (status, _) = post_json(
+            &mut app,
+            "/reports/99/triage",
+            json!({"status": "OPEN", "category": "KNOWN_ISSUE", "author": "qa"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        assert_eq!(ids(&mut app, "/reports?untriaged=true").await, vec![2]);
+        assert_eq!(
+            ids(&mut app, "/projects/1/reports?untriaged=true").await,
+            vec![2]
+        );
+
+        let (status, body) = post_json(
+            &mut app,
+            "/reports/2/triage",
+            json!({"status": "RESOLVED", "category": "BENCH_PROBLEM", "author": "qa"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        assert_eq!(
+            ids(&mut app, "/reports?untriaged=true").await,
+            Vec::<i64>::new()
+        );
+
+        let entries = get_json(&mut app, "/reports/1/triage").await;
+        assert_eq!(
+            entries,
+            json!([{"id": 1,
+                "report_id": 1,
+                "test_step_id": 2,
+                "status": "OPEN",
+                "category": "REAL_DEFECT",
+                "ticket": "PROJ-123",
+                "comment": "Bus times out",
+                "author": "qa",
+                "date": "2014-11-29T10:00:00+01:00"}])
+        );
+        let entry = get_json(&mut app, "/triage/2").await;
+        assert_eq!(entry["report_id"], 2);
+        assert_eq!(entry["test_step_id"], serde_json::Value::Null);
+        assert_eq!(ids(&mut app, "/triage?project=A").await, vec![1, 2]);
+        assert_eq!(
+            ids(&mut app, "/triage?category=BENCH_PROBLEM").await,
+            vec![2]
+        );
+        assert_eq!(ids(&mut app, "/triage?ticket=PROJ-123").await, vec![1]);
+        assert_eq!(
+            ids(&mut app, "/triage?status=OPEN&order=desc").await,
+            vec![1]
+        );
+
+        let (status, _) = send(&mut app, http::Method::DELETE, "/triage/2", Body::empty()).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&mut app, http::Method::GET, "/triage/2", Body::empty()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(ids(&mut app, "/reports?untriaged=true").await, vec![2]);
+        // Every failed step needs an entry unless the whole report has one
+        let (status, _) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/test_steps/1",
+            Body::from(r#"{"verdict": "ERROR"}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ids(&mut app, "/reports?untriaged=true").await, vec![1, 2]);
+
+        // Triage entries are deleted together with their report
+        let (status, _) = send(&mut app, http::Method::DELETE, "/reports/1", Body::empty()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ids(&mut app, "/triage").await, Vec::<i64>::new());
+    }
+
+    #[tokio::test]
+    async fn test_report_diff() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "A").await;
+
+        for (date, duration_ms, steps) in [
+            (
+                "2014-11-28T12:00:00Z",
+                1000,
+                json!([
+                    {"name": "Init", "step_number": 1, "date": "2014-11-28T12:00:00Z", "verdict": "SUCCESS", "duration_ms": 100},
+                    {"name": "Read", "step_number": 2, "date": "2014-11-28T12:00:01Z", "verdict": "FAILED",
+                        "children": [{"name": "Bus", "step_number": 1, "date": "2014-11-28T12:00:01Z", "verdict": "FAILED"}]},
+                    {"name": "Gone", "step_number": 3, "date": "2014-11-28T12:00:02Z", "verdict": "SUCCESS"}
+                ]),
+            ),
+            (
+                "2014-11-29T12:00:00Z",
+                1200,
+                json!([
+                    {"name": "Init", "step_number": 1, "date": "2014-11-29T12:00:00Z", "verdict": "ERROR", "duration_ms": 300},
+                    {"name": "Read", "step_number": 2, "date": "2014-11-29T12:00:01Z", "verdict": "SUCCESS",
+                        "children": [{"name": "Bus", "step_number": 1, "date": "2014-11-29T12:00:01Z", "verdict": "SUCCESS"}]},
+                    {"name": "New", "step_number": 4, "date": "2014-11-29T12:00:02Z", "verdict": "SUCCESS"}
+                ]),
+            ),
+        ] {
+            let (status, body) = post_json(
+                &mut app,
+                "/reports/bulk",
+                json!({"date": date,
+                    "duration_ms": duration_ms,
+                    "project": "A",
+                    "name": "Case.pkg",
+                    "verdict": "SUCCESS",
+                    "test_steps": steps}),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        }
+
+        let diff = get_json(&mut app, "/reports/1/diff/2").await;
+        assert_eq!(diff["old"]["id"], 1);
+        assert_eq!(diff["new"]["id"], 2);
+        assert_eq!(diff["duration_change_ms"], 200);
+        assert_eq!(
+            diff["counts"],
+            json!({"ADDED": 1, "REMOVED": 1, "NEWLY_FAILING": 1, "FIXED": 2})
+        );
+        let changes: Vec<_> = diff["steps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|step| {
+                (
+                    step["name"].as_str().unwrap(),
+                    step["change"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("Init", "NEWLY_FAILING"),
+                ("Read", "FIXED"),
+                ("Bus", "FIXED"),
+                ("New", "ADDED"),
+                ("Gone", "REMOVED"),
+            ]
+        );
+        assert_eq!(diff["steps"][0]["duration_change_ms"], 200);
+        assert_eq!(diff["steps"][2]["path"], json!(["Read"]));
+        assert_eq!(diff["steps"][3]["old"], json!(null));
+        assert_eq!(diff["steps"][4]["new"], json!(null));
+
+        let (status, _) = send(
+            &mut app,
+            http::Method::GET,
+            "/reports/1/diff/3",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_filter_reports() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(db, Features::default());
+        create_project(&mut app, "A").await;
+        create_project(&mut app, "B").await;
+
+        for (project, name, date, verdict, duration_ms) in [
+            (
+                "A",
+                "MyFancyTestCase.pkg",
+                "2014-11-28T12:00:00Z",
+                "SUCCESS",
+                Some(60_000),
+            ),
+            (
+                "A",
+                "MyFancyTestCase_new.pkg",
+                "2015-11-28T12:00:00Z",
+                "FAILED",
+                Some(31_000),
+            ),
+            (
+                "B",
+                "Other_100%.pkg",
+                "2016-11-28T12:00:00Z",
+                "FAILED",
+                None,
+            ),
+            (
+                "A",
+                "Fancy.prj",
+                "2017-11-28T12:00:00Z",
+                "FAILED",
+                Some(30_000),
+            ),
+        ] {
+            let (status, _) = post_json(
+                &mut app,
+                "/reports/bulk",
+                json!({"date": date,
+                    "duration_ms": duration_ms,
+                    "project": project,
+                    "name": name,
+                    "verdict": verdict,
+                    "test_steps": [{"name": "Bus Lesen",
+                        "step_number": 1,
+                        "date": date,
+                        "duration_ms": duration_ms,
+                        "verdict": verdict}]}),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        async fn ids(app: &mut Router, uri: &str) -> (Vec<i64>, String) {
+            let response = app
+                .ready()
+                .await
+                .unwrap()
+                .call(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let total_count = response.headers()[filter::TOTAL_COUNT_HEADER]
+                .to_str()
+                .unwrap()
+                .to_string();
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let items: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+            let ids = items
+                .iter()
+                .map(|item| item["id"].as_i64().unwrap())
+                .collect();
+            (ids, total_count)
+        }
+
+        assert_eq!(
+            ids(&mut app, "/reports").await,
+            (vec![1, 2, 3, 4], "4".into())
+        );
+        assert_eq!(ids(&mut app, "/reports?project=A").await.0, [1, 2, 4]);
+        assert_eq!(
+            ids(&mut app, "/reports?verdict=FAILED&project=A").await.0,
+            [2, 4]
+        );
+        assert_eq!(ids(&mut app, "/reports?name=Fancy").await.0, [1, 2, 4]);
+        assert_eq!(ids(&mut app, "/reports?name=*Fancy*.pkg").await.0, [1, 2]);
+        assert_eq!(ids(&mut app, "/reports?name=100%25").await.0, [3]);
+        assert_eq!(ids(&mut app, "/reports?name=fancy*").await.0, [4]);
+        assert_eq!(ids(&mut app, "/test_steps?name=BUS").await.0, [1, 2, 3, 4]);
+        assert_eq!(
+            ids(
+                &mut app,
+                "/reports?from=2015-01-01T00:00:00Z&to=2016-12-01T00:00:00%2B01:00"
+            )
+            .await
+            .0,
+            [2, 3]
+        );
+        assert_eq!(
+            ids(&mut app, "/reports?sort=date&order=desc&limit=2&offset=1").await,
+            (vec![3, 2], "4".into())
+        );
+
+        assert_eq!(
+            ids(
+                &mut app,
+                "/test_steps?project=A&verdict=FAILED&sort=report_id&order=desc"
+            )
+            .await,
+            (vec![4, 2], "2".into())
+        );
+        assert_eq!(ids(&mut app, "/test_steps?report_id=3").await.0, [3]);
+        assert_eq!(
+            ids(
+                &mut app,
+                "/test_steps?min_duration_ms=30000&sort=duration_ms"
+            )
+            .await,
+            (vec![4, 2, 1], "3".into())
+        );
+        assert_eq!(
+            ids(
+                &mut app,
+                "/reports?min_duration_ms=30001&max_duration_ms=60000&sort=duration_ms&order=desc"
+            )
+            .await
+            .0,
+            [1, 2]
+        );
+        assert_eq!(
+            ids(&mut app, "/test_steps?name=Bus&limit=1").await,
+            (vec![1], "4".into())
+        );
+        // Items with the same sort value are ordered by their id
+        assert_eq!(
+            ids(&mut app, "/reports?sort=project&order=desc").await.0,
+            [3, 4, 2, 1]
+        );
+        assert_eq!(
+            ids(&mut app, "/test_steps?sort=name&limit=2&offset=1")
+                .await
+                .0,
+            [2, 3]
+        );
+
+        let (status, _) = send(
+            &mut app,
+            http::Method::GET,
+            "/reports?verdict=PASSED",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_projects() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(
+            db,
+            Features {
+                create_projects: false,
+                ..Features::default()
+            },
+        );
+
+        let report = json!({"date": "2014-11-28T12:00:09+00:00",
+            "project": "TestProjekt",
+            "name": "TestReport",
+            "verdict": "SUCCESS"});
+        let (status, _) = post_json(&mut app, "/reports", report.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = post_json(
+            &mut app,
+            "/projects",
+            json!({"name": "TestProjekt", "derive_verdict": true}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "1");
+        let (status, _) = post_json(&mut app, "/projects", json!({"name": "TestProjekt"})).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        // Reports follow the verdict setting of their project by default
+        let (status, _) = post_json(&mut app, "/reports", report).await;
+        assert_eq!(status, StatusCode::OK);
+        post_json(
+            &mut app,
+            "/reports/1/test_steps",
+            json!({"name": "Mess Lesen",
+                "step_number": 1,
+                "date": "2014-11-28T12:00:10+00:00",
+                "verdict": "FAILED"}),
+        )
+        .await;
+        let report = get_json(&mut app, "/reports/1").await;
+        assert_eq!(report["project_id"], 1);
+        assert_eq!(report["verdict"], "FAILED");
+
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/projects/1",
+            Body::from(r#"{"name": "Renamed", "description": "Bench 1"}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            json!({
+                "id": 1,
+                "name": "Renamed",
+                "description": "Bench 1",
+                "derive_verdict": true,
+                "baseline_report_id": null,
+                "baseline_test_run_id": null
+            })
+        );
+        assert_eq!(get_json(&mut app, "/reports/1").await["project"], "Renamed");
+        assert_eq!(
+            get_json(&mut app, "/projects")
+                .await
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+        let reports = get_json(&mut app, "/projects/1/reports?verdict=FAILED").await;
+        assert_eq!(reports.as_array().unwrap().len(), 1);
+
+        // `null` clears the description, missing fields are kept
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/projects/1",
+            Body::from(r#"{"description": null}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let project: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(project["description"], serde_json::Value::Null);
+        assert_eq!(project["name"], "Renamed");
+
+        let (status, _) = send(&mut app, http::Method::DELETE, "/projects/1", Body::empty()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        send(&mut app, http::Method::DELETE, "/reports/1", Body::empty()).await;
+        let (status, _) = send(&mut app, http::Method::DELETE, "/projects/1", Body::empty()).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&mut app, http::Method::GET, "/projects/1", Body::empty()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_update_report_and_steps() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(
+            db,
+            Features {
+                create_projects: false,
+                ..Features::default()
+            },
+        );
+        create_project(&mut app, "TestProjekt").await;
+        create_project(&mut app, "OtherProjekt").await;
+
+        post_json(
+            &mut app,
+            "/reports/bulk",
+            json!({"date": "2014-11-28T12:00:09+00:00",
+            "project": "TestProjekt",
+            "name": "TestReport",
+            "verdict": "SUCCESS",
+            "derive_verdict": true,
+            "test_steps": [
+                {"name": "Bus Lesen",
+                "step_number": 1,
+                "date": "2014-11-28T12:00:10+00:00",
+                "verdict": "SUCCESS"},
+                {"name": "Mess Lesen",
+                "step_number": 2,
+                "date": "2014-11-28T12:00:11+00:00",
+                "verdict": "FAILED"}
+            ]}),
+        )
+        .await;
+        assert_eq!(get_json(&mut app, "/reports/1").await["verdict"], "FAILED");
+
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/reports/1",
+            Body::from(
+                r#"{"name": "Renamed", "project": "OtherProjekt", "derive_verdict": false, "duration_ms": 500}"#,
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["name"], "Renamed");
+        assert_eq!(report["project"], "OtherProjekt");
+        assert_eq!(report["project_id"], 2);
+        assert_eq!(report["verdict"], "SUCCESS");
+        assert_eq!(report["duration_ms"], 500);
+        assert_eq!(report["date"], "2014-11-28T12:00:09+00:00");
+
+        // Same validation as for new reports
+        for patch in [
+            r#"{"project": "Unknown"}"#,
+            r#"{"verdict": "PASSED"}"#,
+            r#"{"duration_ms": -1}"#,
+        ] {
+            let (status, _) = send(
+                &mut app,
+                http::Method::PATCH,
+                "/reports/1",
+                Body::from(patch),
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+        }
+        let (status, _) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/reports/2",
+            Body::from("{}"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(
+            &mut app,
+            http::Method::PUT,
+            "/reports/1",
+            Body::from(r#"{"name": "Replaced", "project": "TestProjekt", "verdict": "ERROR"}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, body) = send(
+            &mut app,
+            http::Method::PUT,
+            "/reports/1",
+            Body::from(
+                json!({"date": "2015-11-28T12:00:09+00:00",
+                    "project": "TestProjekt",
+                    "name": "Replaced",
+                    "verdict": "ERROR"})
+                .to_string(),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["name"], "Replaced");
+        assert_eq!(report["verdict"], "ERROR");
+        assert_eq!(report["declared_verdict"], "ERROR");
+        // Fields missing from a replacement are reset
+        assert_eq!(report["duration_ms"], serde_json::Value::Null);
+        assert_eq!(report["derive_verdict"], serde_json::Value::Null);
+
+        // Changing steps updates the computed verdict of their report
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/test_steps/2",
+            Body::from(r#"{"verdict": "INCONCLUSIVE", "output": "Timeout"}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let step: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(step["verdict"], "INCONCLUSIVE");
+        assert_eq!(step["output"], "Timeout");
+        assert_eq!(step["name"], "Mess Lesen");
+        assert_eq!(
+            get_json(&mut app, "/reports/1").await["computed_verdict"],
+            "INCONCLUSIVE"
+        );
+        // `null` clears a field, missing fields are kept
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/test_steps/2",
+            Body::from(r#"{"output": null}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let step: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(step["output"], serde_json::Value::Null);
+        assert_eq!(step["verdict"], "INCONCLUSIVE");
+        let (status, body) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/test_steps/2",
+            Body::from(r#"{"duration_ms": -1}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["details"]["field"], "duration_ms");
+        for (patch, duration) in [
+            (r#"{"duration_ms": 0}"#, json!(0)),
+            (r#"{"duration_ms": null}"#, json!(null)),
+        ] {
+            let (status, body) = send(
+                &mut app,
+                http::Method::PATCH,
+                "/test_steps/2",
+                Body::from(patch),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            let step: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(step["duration_ms"], duration);
+        }
+
+        let (status, _) = send(
+            &mut app,
+            http::Method::DELETE,
+            "/test_steps/2",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            get_json(&mut app, "/reports/1").await["computed_verdict"],
+            "SUCCESS"
+        );
+        let (status, _) = send(
+            &mut app,
+            http::Method::DELETE,
+            "/test_steps/2",
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(
+            &mut app,
+            http::Method::PATCH,
+            "/test_steps/2",
+            Body::from("{}"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_features() {
+        let (db, _tmp_file) = setup_empty_temp_database().await;
+        let mut app = test_app(
+            db,
+            Features {
+                import: false,
+                derive_verdict: true,
+                create_projects: true,
+            },
+        );
+        create_project(&mut app, "TestProjekt").await;
+        assert_eq!(
+            get_json(&mut app, "/projects/1").await["derive_verdict"],
+            true
+        );
+        let (status, _) = post_json(
+            &mut app,
+            "/projects",
+            json!({"name": "OtherProjekt", "derive_verdict": false}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            get_json(&mut app, "/projects/2").await["derive_verdict"],
+            false
+        );
+
+        let (status, _) = send(
+            &mut app,
+            http::Method::POST,
+            "/import/junit?project=TestProjekt",
+            Body::from("<testsuites/>"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // Reports of unknown projects create them
+        let (status, _) = post_json(
+            &mut app,
+            "/reports",
+            json!({"date": "2014-11-28T12:00:09+00:00",
+                "project": "NewProjekt",
+                "name": "TestReport",
+                "verdict": "SUCCESS"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let project = get_json(&mut app, "/projects/3").await;
+        assert_eq!(project["name"], "NewProjekt");
+        assert_eq!(project["derive_verdict"], true);
+        assert_eq!(get_json(&mut app, "/reports/1").await["project_id"], 3);
     }
 }
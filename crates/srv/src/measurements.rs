@@ -0,0 +1,148 @@
+//! Endpoints for the measurements taken by test steps.
+
+use entities::{prelude::*, *};
+
+use crate::error::RgError;
+use crate::extract::{self, Path, Query};
+use crate::filter::{self, MeasurementFilter};
+use axum::{extract::State, Json};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A measurement to be stored for a test step
+#[derive(Deserialize)]
+pub(crate) struct NewMeasurement {
+    name: String,
+    value: f64,
+    #[serde(default)]
+    unit: Option<String>,
+    #[serde(default)]
+    lower_limit: Option<f64>,
+    #[serde(default)]
+    upper_limit: Option<f64>,
+    /// Derived from the limits if not given
+    #[serde(default)]
+    passed: Option<bool>,
+}
+
+fn new_measurement_model(m: NewMeasurement, test_step_id: i32) -> measurement::ActiveModel {
+    let passed = m
+        .passed
+        .unwrap_or_else(|| measurement::within_limits(m.value, m.lower_limit, m.upper_limit));
+    measurement::ActiveModel {
+        test_step_id: ActiveValue::Set(test_step_id),
+        name: ActiveValue::Set(m.name),
+        value: ActiveValue::Set(m.value),
+        unit: ActiveValue::Set(m.unit),
+        lower_limit: ActiveValue::Set(m.lower_limit),
+        upper_limit: ActiveValue::Set(m.upper_limit),
+        passed: ActiveValue::Set(passed),
+        ..Default::default()
+    }
+}
+
+/// Stores all measurements of a test step with a single statement.
+pub(crate) async fn insert_measurements<C: ConnectionTrait>(
+    db: &C,
+    test_step_id: i32,
+    measurements: Vec<NewMeasurement>,
+) -> Result<(), DbErr> {
+    if !measurements.is_empty() {
+        Measurement::insert_many(
+            measurements
+                .into_iter()
+                .map(|m| new_measurement_model(m, test_step_id)),
+        )
+        .exec(db)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn find_for_teststep<C: ConnectionTrait>(
+    db: &C,
+    test_step_id: i32,
+) -> Result<Vec<measurement::Model>, DbErr> {
+    Measurement::find()
+        .filter(measurement::Column::TestStepId.eq(test_step_id))
+        .order_by_asc(measurement::Column::Id)
+        .all(db)
+        .await
+}
+
+/// Adds any number of measurements to a test step and returns all its
+/// measurements.
+pub(crate) async fn add_measurements(
+    Path(teststep_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+    measurements: String,
+) -> Result<Json<Vec<measurement::Model>>, RgError> {
+    let measurements: Vec<NewMeasurement> = extract::json(&measurements)?;
+    let ts = crate::find_teststep(&db, teststep_id).await?;
+    Ok(Json(
+        db.transaction(|txn| {
+            Box::pin(async move {
+                insert_measurements(txn, ts.id, measurements).await?;
+                find_for_teststep(txn, ts.id).await
+            })
+        })
+        .await?,
+    ))
+}
+
+pub(crate) async fn get_measurements_for_teststep(
+    Path(teststep_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<measurement::Model>>, RgError> {
+    let ts = crate::find_teststep(&db, teststep_id).await?;
+    Ok(Json(find_for_teststep(&db, ts.id).await?))
+}
+
+/// A measurement together with when and in which report it was taken
+#[derive(Serialize)]
+pub(crate) struct MeasurementPoint {
+    #[serde(flatten)]
+    measurement: measurement::Model,
+    report_id: i32,
+    /// Start of the test step that took the measurement
+    date: DateTimeWithTimeZone,
+}
+
+/// Returns all measurements with the given name across reports, ordered by
+/// the date of their test step to follow a value over time.
+pub(crate) async fn get_measurements(
+    Query(filter): Query<MeasurementFilter>,
+    State(db): State<DatabaseConnection>,
+) -> Result<([(&'static str, String); 1], Json<Vec<MeasurementPoint>>), RgError> {
+    let select = filter.select();
+    let total_count = select.clone().count(&db).await?;
+    let measurements = filter.paginate(select).all(&db).await?;
+
+    let step_ids: Vec<_> = measurements.iter().map(|m| m.test_step_id).collect();
+    let steps: HashMap<_, _> = TestStep::find()
+        .filter(test_step::Column::Id.is_in(step_ids))
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(|step| (step.id, step.with_original_offset()))
+        .collect();
+    let points = measurements
+        .into_iter()
+        .filter_map(|measurement| {
+            let step = steps.get(&measurement.test_step_id)?;
+            Some(MeasurementPoint {
+                report_id: step.report_id,
+                date: step.date,
+                measurement,
+            })
+        })
+        .collect();
+    Ok((
+        [(filter::TOTAL_COUNT_HEADER, total_count.to_string())],
+        Json(points),
+    ))
+}
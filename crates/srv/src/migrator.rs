@@ -1,5 +1,22 @@
 mod m20220812_000001_create_report_table;
 mod m20220812_000002_create_test_step_table;
+mod m20230501_000003_normalize_verdicts;
+mod m20230501_000004_add_report_verdict_sources;
+mod m20230501_000005_add_test_step_output;
+mod m20230501_000006_add_test_step_duration;
+mod m20230501_000007_create_project_table;
+mod m20230501_000008_use_timestamptz_on_postgres;
+mod m20230501_000009_add_date_offsets;
+mod m20230501_000010_add_report_duration;
+mod m20230501_000011_add_test_step_parent;
+mod m20230501_000012_add_test_step_failure_details;
+mod m20230501_000013_create_attachment_table;
+mod m20230501_000014_create_measurement_table;
+mod m20230501_000015_create_report_metadata_tables;
+mod m20230501_000016_create_test_run_table;
+mod m20230501_000017_add_baselines;
+mod m20230501_000018_create_triage_table;
+mod m20230501_000019_add_report_output;
 
 use sea_orm_migration::prelude::*;
 
@@ -11,6 +28,23 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20220812_000001_create_report_table::Migration),
             Box::new(m20220812_000002_create_test_step_table::Migration),
+            Box::new(m20230501_000003_normalize_verdicts::Migration),
+            Box::new(m20230501_000004_add_report_verdict_sources::Migration),
+            Box::new(m20230501_000005_add_test_step_output::Migration),
+            Box::new(m20230501_000006_add_test_step_duration::Migration),
+            Box::new(m20230501_000007_create_project_table::Migration),
+            Box::new(m20230501_000008_use_timestamptz_on_postgres::Migration),
+            Box::new(m20230501_000009_add_date_offsets::Migration),
+            Box::new(m20230501_000010_add_report_duration::Migration),
+            Box::new(m20230501_000011_add_test_step_parent::Migration),
+            Box::new(m20230501_000012_add_test_step_failure_details::Migration),
+            Box::new(m20230501_000013_create_attachment_table::Migration),
+            Box::new(m20230501_000014_create_measurement_table::Migration),
+            Box::new(m20230501_000015_create_report_metadata_tables::Migration),
+            Box::new(m20230501_000016_create_test_run_table::Migration),
+            Box::new(m20230501_000017_add_baselines::Migration),
+            Box::new(m20230501_000018_create_triage_table::Migration),
+            Box::new(m20230501_000019_add_report_output::Migration),
         ]
     }
 }
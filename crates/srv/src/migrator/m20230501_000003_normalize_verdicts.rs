@@ -0,0 +1,76 @@
+use crate::migrator::m20220812_000001_create_report_table::Report;
+use crate::migrator::m20220812_000002_create_test_step_table::TestStep;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000003_normalize_verdicts.rs"
+    }
+}
+
+/// Spellings found in existing rows and the verdict they are mapped to.
+/// Everything else ends up as `NONE`.
+const VERDICT_ALIASES: [(&str, &[&str]); 5] = [
+    ("SUCCESS", &["SUCCESS", "PASSED", "PASS", "OK"]),
+    ("INCONCLUSIVE", &["INCONCLUSIVE"]),
+    ("FAILED", &["FAILED", "FAIL", "FAILURE"]),
+    ("ERROR", &["ERROR", "ERRORED"]),
+    ("SKIPPED", &["SKIPPED", "SKIP", "IGNORED"]),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Map all verdicts to the known values.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        normalize(
+            manager,
+            Report::Table.into_iden(),
+            Report::Verdict.into_iden(),
+        )
+        .await?;
+        normalize(
+            manager,
+            TestStep::Table.into_iden(),
+            TestStep::Verdict.into_iden(),
+        )
+        .await
+    }
+
+    // Define how to rollback this migration: The original spelling is lost, so there is nothing to do.
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
+
+async fn normalize(
+    manager: &SchemaManager<'_>,
+    table: DynIden,
+    column: DynIden,
+) -> Result<(), DbErr> {
+    for (verdict, aliases) in VERDICT_ALIASES {
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(table.clone())
+                    .value(column.clone(), verdict)
+                    .and_where(
+                        Expr::expr(Func::upper(Expr::col(column.clone())))
+                            .is_in(aliases.iter().copied()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+    }
+    let known = VERDICT_ALIASES.iter().map(|(verdict, _)| *verdict);
+    manager
+        .exec_stmt(
+            Query::update()
+                .table(table.clone())
+                .value(column.clone(), "NONE")
+                .and_where(Expr::col(column.clone()).is_not_in(known))
+                .to_owned(),
+        )
+        .await
+}
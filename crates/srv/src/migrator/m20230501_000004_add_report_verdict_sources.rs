@@ -0,0 +1,123 @@
+use crate::migrator::m20220812_000002_create_test_step_table::TestStep;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000004_add_report_verdict_sources.rs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Add the declared and computed verdict to the Report table.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only supports a single change per ALTER TABLE statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Report::Table)
+                    .add_column(
+                        ColumnDef::new(Report::DeclaredVerdict)
+                            .string()
+                            .not_null()
+                            .default("NONE"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Report::Table)
+                    .add_column(ColumnDef::new(Report::ComputedVerdict).string().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Report::Table)
+                    .add_column(ColumnDef::new(Report::DeriveVerdict).boolean().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        let severity = Expr::case(
+            Expr::col((TestStep::Table, TestStep::Verdict)).eq("ERROR"),
+            5,
+        )
+        .case(
+            Expr::col((TestStep::Table, TestStep::Verdict)).eq("FAILED"),
+            4,
+        )
+        .case(
+            Expr::col((TestStep::Table, TestStep::Verdict)).eq("INCONCLUSIVE"),
+            3,
+        )
+        .case(
+            Expr::col((TestStep::Table, TestStep::Verdict)).eq("SUCCESS"),
+            2,
+        )
+        .case(
+            Expr::col((TestStep::Table, TestStep::Verdict)).eq("SKIPPED"),
+            1,
+        )
+        .finally(0);
+        let worst_step_verdict = Query::select()
+            .column((TestStep::Table, TestStep::Verdict))
+            .from(TestStep::Table)
+            .and_where(
+                Expr::col((TestStep::Table, TestStep::ReportId))
+                    .equals((Report::Table, Report::Id)),
+            )
+            .order_by_expr(severity.into(), Order::Desc)
+            .limit(1)
+            .to_owned();
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Report::Table)
+                    .value(Report::DeclaredVerdict, Expr::col(Report::Verdict))
+                    .value(
+                        Report::ComputedVerdict,
+                        SimpleExpr::SubQuery(
+                            None,
+                            Box::new(worst_step_verdict.into_sub_query_statement()),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the added columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            Report::DeclaredVerdict,
+            Report::ComputedVerdict,
+            Report::DeriveVerdict,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Report::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum Report {
+    Table,
+    Id,
+    Verdict,
+    DeclaredVerdict,
+    ComputedVerdict,
+    DeriveVerdict,
+}
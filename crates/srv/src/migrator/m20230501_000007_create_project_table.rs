@@ -0,0 +1,136 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000007_create_project_table.rs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the Project table and reference it from the Report table.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Project::Table)
+                    .col(
+                        ColumnDef::new(Project::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Project::Name)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Project::Description).text().null())
+                    .col(
+                        ColumnDef::new(Project::DeriveVerdict)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Project::Table)
+                    .columns([Project::Name])
+                    .select_from(
+                        Query::select()
+                            .distinct()
+                            .column(Report::Project)
+                            .from(Report::Table)
+                            .to_owned(),
+                    )
+                    .map_err(|e| DbErr::Migration(e.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+
+        // SQLite cannot add foreign keys to existing tables, but allows a
+        // reference in the column definition.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Report::Table)
+                    .add_column(
+                        ColumnDef::new(Report::ProjectId)
+                            .integer()
+                            .null()
+                            .extra(format!(
+                                "REFERENCES {} ({})",
+                                Project::Table.to_string(),
+                                Project::Id.to_string()
+                            )),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Report::Table)
+                    .value(
+                        Report::ProjectId,
+                        SimpleExpr::SubQuery(
+                            None,
+                            Box::new(
+                                Query::select()
+                                    .column((Project::Table, Project::Id))
+                                    .from(Project::Table)
+                                    .and_where(
+                                        Expr::col((Project::Table, Project::Name))
+                                            .equals((Report::Table, Report::Project)),
+                                    )
+                                    .to_owned()
+                                    .into_sub_query_statement(),
+                            ),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the reference and the Project table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Report::Table)
+                    .drop_column(Report::ProjectId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Project::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Project {
+    Table,
+    Id,
+    Name,
+    Description,
+    DeriveVerdict,
+}
+
+#[derive(Iden)]
+pub enum Report {
+    Table,
+    Project,
+    ProjectId,
+}
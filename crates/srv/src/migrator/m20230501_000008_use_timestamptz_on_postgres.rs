@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DbBackend};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000008_use_timestamptz_on_postgres.rs"
+    }
+}
+
+/// Tables with a `date` column
+const TABLES: [&str; 2] = ["report", "test_step"];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Store dates with their time zone on PostgreSQL.
+    //
+    // `date_time()` is a `timestamp` on PostgreSQL, which cannot be decoded
+    // as `DateTimeWithTimeZone`. SQLite does not care about column types.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+        for table in TABLES {
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    r#"ALTER TABLE "{table}" ALTER COLUMN "date" TYPE timestamptz USING "date" AT TIME ZONE 'UTC'"#
+                ))
+                .await?;
+        }
+        Ok(())
+    }
+
+    // Define how to rollback this migration: Convert the dates back to UTC timestamps.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+        for table in TABLES {
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    r#"ALTER TABLE "{table}" ALTER COLUMN "date" TYPE timestamp USING "date" AT TIME ZONE 'UTC'"#
+                ))
+                .await?;
+        }
+        Ok(())
+    }
+}
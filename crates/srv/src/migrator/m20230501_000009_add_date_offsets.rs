@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DbBackend};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000009_add_date_offsets.rs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Add the original UTC offset of the dates to the Report and TestStep tables.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [Report::Table.into_iden(), TestStep::Table.into_iden()] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table.clone())
+                        .add_column(
+                            ColumnDef::new(Report::DateOffset)
+                                .integer()
+                                .not_null()
+                                .default(0),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+
+            // SQLite keeps the dates as RFC 3339 text including the offset,
+            // PostgreSQL only keeps the instant.
+            if manager.get_database_backend() == DbBackend::Sqlite {
+                manager
+                    .get_connection()
+                    .execute_unprepared(&format!(
+                        "UPDATE \"{}\" SET date_offset = CASE \
+                             WHEN substr(date, -6, 1) IN ('+', '-') AND substr(date, -3, 1) = ':' \
+                             THEN (CASE substr(date, -6, 1) WHEN '-' THEN -1 ELSE 1 END) \
+                                 * (CAST(substr(date, -5, 2) AS INTEGER) * 3600 \
+                                     + CAST(substr(date, -2, 2) AS INTEGER) * 60) \
+                             ELSE 0 \
+                         END",
+                        table.to_string()
+                    ))
+                    .await?;
+                // Store all dates in UTC so that they can be compared as text,
+                // in the format written by sqlx.
+                manager
+                    .get_connection()
+                    .execute_unprepared(&format!(
+                        "UPDATE \"{}\" SET date = \
+                             replace(strftime('%Y-%m-%dT%H:%M:%f', date), '.000', '') || '+00:00'",
+                        table.to_string()
+                    ))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Define how to rollback this migration: Drop the added columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [Report::Table.into_iden(), TestStep::Table.into_iden()] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Report::DateOffset)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum Report {
+    Table,
+    DateOffset,
+}
+
+#[derive(Iden)]
+pub enum TestStep {
+    Table,
+}
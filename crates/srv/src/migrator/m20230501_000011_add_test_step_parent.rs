@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000011_add_test_step_parent.rs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Let test steps reference their parent step.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // See m20230501_000007_create_project_table for the reference
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TestStep::Table)
+                    .add_column(
+                        ColumnDef::new(TestStep::ParentId)
+                            .integer()
+                            .null()
+                            .extra(format!(
+                                "REFERENCES {} ({})",
+                                TestStep::Table.to_string(),
+                                TestStep::Id.to_string()
+                            )),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the added column.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TestStep::Table)
+                    .drop_column(TestStep::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum TestStep {
+    Table,
+    Id,
+    ParentId,
+}
@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000012_add_test_step_failure_details.rs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Add the message, expected and actual values and the failure category to the TestStep table.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only supports a single change per ALTER TABLE statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TestStep::Table)
+                    .add_column(ColumnDef::new(TestStep::Message).text().null())
+                    .to_owned(),
+            )
+            .await?;
+        for column in [TestStep::Expected, TestStep::Actual, TestStep::Unit] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(TestStep::Table)
+                        .add_column(ColumnDef::new(column).string().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TestStep::Table)
+                    .add_column(ColumnDef::new(TestStep::FailureCategory).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the added columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            TestStep::Message,
+            TestStep::Expected,
+            TestStep::Actual,
+            TestStep::Unit,
+            TestStep::FailureCategory,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(TestStep::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum TestStep {
+    Table,
+    Message,
+    Expected,
+    Actual,
+    Unit,
+    FailureCategory,
+}
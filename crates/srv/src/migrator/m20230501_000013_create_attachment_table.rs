@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000013_create_attachment_table.rs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the Attachment table.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Attachment::Table)
+                    .col(
+                        ColumnDef::new(Attachment::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Attachment::ReportId).integer().not_null())
+                    .col(ColumnDef::new(Attachment::TestStepId).integer().null())
+                    .col(ColumnDef::new(Attachment::Filename).string().not_null())
+                    .col(ColumnDef::new(Attachment::MimeType).string().not_null())
+                    .col(ColumnDef::new(Attachment::Size).big_integer().not_null())
+                    .col(ColumnDef::new(Attachment::Sha256).string().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-attachment-report_id")
+                            .from(Attachment::Table, Attachment::ReportId)
+                            .to(Report::Table, Report::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-attachment-test_step_id")
+                            .from(Attachment::Table, Attachment::TestStepId)
+                            .to(TestStep::Table, TestStep::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Blobs are shared by all attachments with the same content
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-attachment-sha256")
+                    .table(Attachment::Table)
+                    .col(Attachment::Sha256)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the Attachment table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Attachment::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Attachment {
+    Table,
+    Id,
+    ReportId,
+    TestStepId,
+    Filename,
+    MimeType,
+    Size,
+    Sha256,
+}
+
+#[derive(Iden)]
+pub enum Report {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+pub enum TestStep {
+    Table,
+    Id,
+}
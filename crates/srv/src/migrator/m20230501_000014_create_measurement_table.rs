@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000014_create_measurement_table.rs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the Measurement table.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Measurement::Table)
+                    .col(
+                        ColumnDef::new(Measurement::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Measurement::TestStepId).integer().not_null())
+                    .col(ColumnDef::new(Measurement::Name).string().not_null())
+                    .col(ColumnDef::new(Measurement::Value).double().not_null())
+                    .col(ColumnDef::new(Measurement::Unit).string().null())
+                    .col(ColumnDef::new(Measurement::LowerLimit).double().null())
+                    .col(ColumnDef::new(Measurement::UpperLimit).double().null())
+                    .col(ColumnDef::new(Measurement::Passed).boolean().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-measurement-test_step_id")
+                            .from(Measurement::Table, Measurement::TestStepId)
+                            .to(TestStep::Table, TestStep::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Measurements are queried by name across reports
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-measurement-name")
+                    .table(Measurement::Table)
+                    .col(Measurement::Name)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the Measurement table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Measurement::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Measurement {
+    Table,
+    Id,
+    TestStepId,
+    Name,
+    Value,
+    Unit,
+    LowerLimit,
+    UpperLimit,
+    Passed,
+}
+
+#[derive(Iden)]
+pub enum TestStep {
+    Table,
+    Id,
+}
@@ -0,0 +1,141 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000015_create_report_metadata_tables.rs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the ReportProperty and ReportTag tables.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReportProperty::Table)
+                    .col(
+                        ColumnDef::new(ReportProperty::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ReportProperty::ReportId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ReportProperty::Key).string().not_null())
+                    .col(ColumnDef::new(ReportProperty::Value).string().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-report_property-report_id")
+                            .from(ReportProperty::Table, ReportProperty::ReportId)
+                            .to(Report::Table, Report::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-report_property-report_id-key")
+                    .table(ReportProperty::Table)
+                    .col(ReportProperty::ReportId)
+                    .col(ReportProperty::Key)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        // Reports are filtered by the value of a property
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-report_property-key-value")
+                    .table(ReportProperty::Table)
+                    .col(ReportProperty::Key)
+                    .col(ReportProperty::Value)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReportTag::Table)
+                    .col(
+                        ColumnDef::new(ReportTag::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ReportTag::ReportId).integer().not_null())
+                    .col(ColumnDef::new(ReportTag::Tag).string().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-report_tag-report_id")
+                            .from(ReportTag::Table, ReportTag::ReportId)
+                            .to(Report::Table, Report::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-report_tag-report_id-tag")
+                    .table(ReportTag::Table)
+                    .col(ReportTag::ReportId)
+                    .col(ReportTag::Tag)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-report_tag-tag")
+                    .table(ReportTag::Table)
+                    .col(ReportTag::Tag)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop both tables.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ReportTag::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ReportProperty::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum ReportProperty {
+    Table,
+    Id,
+    ReportId,
+    Key,
+    Value,
+}
+
+#[derive(Iden)]
+pub enum ReportTag {
+    Table,
+    Id,
+    ReportId,
+    Tag,
+}
+
+#[derive(Iden)]
+pub enum Report {
+    Table,
+    Id,
+}
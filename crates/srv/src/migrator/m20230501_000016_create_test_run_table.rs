@@ -0,0 +1,134 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000016_create_test_run_table.rs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the TestRun table and reference it from the Report table.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TestRun::Table)
+                    .col(
+                        ColumnDef::new(TestRun::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TestRun::ProjectId).integer().not_null())
+                    .col(ColumnDef::new(TestRun::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(TestRun::Start)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TestRun::StartOffset)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(TestRun::End)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(TestRun::EndOffset).integer().null())
+                    .col(ColumnDef::new(TestRun::Trigger).string().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-test_run-project_id")
+                            .from(TestRun::Table, TestRun::ProjectId)
+                            .to(Project::Table, Project::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // See m20230501_000007_create_project_table for the reference
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Report::Table)
+                    .add_column(
+                        ColumnDef::new(Report::TestRunId)
+                            .integer()
+                            .null()
+                            .extra(format!(
+                                "REFERENCES {} ({})",
+                                TestRun::Table.to_string(),
+                                TestRun::Id.to_string()
+                            )),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The summary of a run aggregates all its reports
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-report-test_run_id")
+                    .table(Report::Table)
+                    .col(Report::TestRunId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the reference and the TestRun table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-report-test_run_id")
+                    .table(Report::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Report::Table)
+                    .drop_column(Report::TestRunId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(TestRun::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum TestRun {
+    Table,
+    Id,
+    ProjectId,
+    Name,
+    Start,
+    StartOffset,
+    End,
+    EndOffset,
+    Trigger,
+}
+
+#[derive(Iden)]
+pub enum Project {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+pub enum Report {
+    Table,
+    TestRunId,
+}
@@ -0,0 +1,125 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000017_add_baselines.rs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Reference the baseline from the Project table and add the baseline status to the Report and TestStep tables.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // See m20230501_000007_create_project_table for the references,
+        // SQLite only supports a single change per ALTER TABLE statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Project::Table)
+                    .add_column(
+                        ColumnDef::new(Project::BaselineReportId)
+                            .integer()
+                            .null()
+                            .extra(format!(
+                                "REFERENCES {} ({})",
+                                Report::Table.to_string(),
+                                Report::Id.to_string()
+                            )),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Project::Table)
+                    .add_column(
+                        ColumnDef::new(Project::BaselineTestRunId)
+                            .integer()
+                            .null()
+                            .extra(format!(
+                                "REFERENCES {} ({})",
+                                TestRun::Table.to_string(),
+                                TestRun::Id.to_string()
+                            )),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Report::Table)
+                    .add_column(ColumnDef::new(Report::BaselineStatus).string().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TestStep::Table)
+                    .add_column(ColumnDef::new(TestStep::BaselineStatus).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the added columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TestStep::Table)
+                    .drop_column(TestStep::BaselineStatus)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Report::Table)
+                    .drop_column(Report::BaselineStatus)
+                    .to_owned(),
+            )
+            .await?;
+        for column in [Project::BaselineTestRunId, Project::BaselineReportId] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Project::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum Project {
+    Table,
+    BaselineReportId,
+    BaselineTestRunId,
+}
+
+#[derive(Iden)]
+pub enum Report {
+    Table,
+    Id,
+    BaselineStatus,
+}
+
+#[derive(Iden)]
+pub enum TestRun {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+pub enum TestStep {
+    Table,
+    BaselineStatus,
+}
@@ -0,0 +1,105 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230501_000018_create_triage_table.rs"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the Triage table.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Triage::Table)
+                    .col(
+                        ColumnDef::new(Triage::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Triage::ReportId).integer().not_null())
+                    .col(ColumnDef::new(Triage::TestStepId).integer().null())
+                    .col(ColumnDef::new(Triage::Status).string().not_null())
+                    .col(ColumnDef::new(Triage::Category).string().not_null())
+                    .col(ColumnDef::new(Triage::Ticket).string().null())
+                    .col(ColumnDef::new(Triage::Comment).text().null())
+                    .col(ColumnDef::new(Triage::Author).string().not_null())
+                    .col(
+                        ColumnDef::new(Triage::Date)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Triage::DateOffset)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-triage-report_id")
+                            .from(Triage::Table, Triage::ReportId)
+                            .to(Report::Table, Report::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-triage-test_step_id")
+                            .from(Triage::Table, Triage::TestStepId)
+                            .to(TestStep::Table, TestStep::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Untriaged failures are found by the reports without any entry
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-triage-report_id")
+                    .table(Triage::Table)
+                    .col(Triage::ReportId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the Triage table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Triage::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Triage {
+    Table,
+    Id,
+    ReportId,
+    TestStepId,
+    Status,
+    Category,
+    Ticket,
+    Comment,
+    Author,
+    Date,
+    DateOffset,
+}
+
+#[derive(Iden)]
+pub enum Report {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+pub enum TestStep {
+    Table,
+    Id,
+}
@@ -0,0 +1,227 @@
+//! Endpoints for managing projects.
+
+use entities::{prelude::*, *};
+
+use crate::config::Features;
+use crate::error::{RgError, WithStatusCode};
+use crate::extract::{self, Path, Query};
+use crate::filter::{self, ReportFilter};
+use axum::{
+    extract::{RawQuery, State},
+    http::StatusCode,
+    Json,
+};
+use eyre::eyre;
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait,
+    DatabaseConnection, DbErr, EntityTrait, ModelTrait, PaginatorTrait, QueryFilter,
+    TransactionTrait,
+};
+use serde::Deserialize;
+
+/// Finds a project by name. Unknown projects are created if the
+/// `create_projects` feature is enabled, otherwise this fails with
+/// `400 Bad Request`.
+pub(crate) async fn find_by_name<C: ConnectionTrait>(
+    db: &C,
+    features: Features,
+    name: &str,
+) -> Result<project::Model, RgError> {
+    let find = Project::find().filter(project::Column::Name.eq(name));
+    if let Some(project) = find.clone().one(db).await? {
+        return Ok(project);
+    }
+    if features.create_projects {
+        let project_model = project::ActiveModel {
+            name: ActiveValue::Set(name.to_string()),
+            derive_verdict: ActiveValue::Set(features.derive_verdict),
+            ..Default::default()
+        };
+        // Another upload may create the same project concurrently.
+        Project::insert(project_model)
+            .on_conflict(
+                OnConflict::column(project::Column::Name)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+    }
+    find.one(db)
+        .await?
+        .ok_or_else(|| eyre!("Unknown project {name}, create it at /projects first"))
+        .with_status_code(StatusCode::BAD_REQUEST)
+}
+
+pub(crate) async fn find_by_id<C: ConnectionTrait>(
+    db: &C,
+    project_id: i32,
+) -> Result<project::Model, RgError> {
+    Project::find_by_id(project_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| eyre!("Could not find project with id {project_id}!"))
+        .with_status_code(StatusCode::NOT_FOUND)
+}
+
+async fn ensure_name_is_free<C: ConnectionTrait>(db: &C, name: &str) -> Result<(), RgError> {
+    if Project::find()
+        .filter(project::Column::Name.eq(name))
+        .one(db)
+        .await?
+        .is_some()
+    {
+        return Err(eyre!("Project {name} already exists")).with_status_code(StatusCode::CONFLICT);
+    }
+    Ok(())
+}
+
+pub(crate) async fn get_projects(
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<project::Model>>, RgError> {
+    Ok(Json(Project::find().all(&db).await?))
+}
+
+pub(crate) async fn get_single_project(
+    Path(project_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<project::Model>, RgError> {
+    Ok(Json(find_by_id(&db, project_id).await?))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct NewProject {
+    name: String,
+    description: Option<String>,
+    /// Defaults to the `derive_verdict` feature of the server
+    derive_verdict: Option<bool>,
+}
+
+pub(crate) async fn add_project(
+    State(db): State<DatabaseConnection>,
+    State(features): State<Features>,
+    project: String,
+) -> Result<String, RgError> {
+    let project: NewProject = extract::json(&project)?;
+    ensure_name_is_free(&db, &project.name).await?;
+
+    let project_model = project::ActiveModel {
+        name: ActiveValue::Set(project.name),
+        description: ActiveValue::Set(project.description),
+        derive_verdict: ActiveValue::Set(project.derive_verdict.unwrap_or(features.derive_verdict)),
+        ..Default::default()
+    };
+    let res = Project::insert(project_model).exec(&db).await?;
+    Ok(res.last_insert_id.to_string())
+}
+
+/// Changes to a project, fields that are `None` are left untouched and
+/// nullable fields set to `null` are cleared
+#[derive(Deserialize)]
+pub(crate) struct ProjectPatch {
+    name: Option<String>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    description: Option<Option<String>>,
+    derive_verdict: Option<bool>,
+}
+
+/// Updates the given fields of a project, renaming a project renames it in
+/// all its reports as well.
+pub(crate) async fn update_project(
+    Path(project_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+    patch: String,
+) -> Result<Json<project::Model>, RgError> {
+    let patch: ProjectPatch = extract::json(&patch)?;
+    let project = find_by_id(&db, project_id).await?;
+    if let Some(name) = patch.name.as_deref().filter(|name| *name != project.name) {
+        ensure_name_is_free(&db, name).await?;
+    }
+
+    let project = db
+        .transaction(|txn| {
+            Box::pin(async move {
+                let mut project_model: project::ActiveModel = project.into();
+                if let Some(name) = patch.name {
+                    Report::update_many()
+                        .col_expr(report::Column::Project, name.clone().into())
+                        .filter(report::Column::ProjectId.eq(project_id))
+                        .exec(txn)
+                        .await?;
+                    project_model.name = ActiveValue::Set(name);
+                }
+                if let Some(description) = patch.description {
+                    project_model.description = ActiveValue::Set(description);
+                }
+                if let Some(derive_verdict) = patch.derive_verdict {
+                    project_model.derive_verdict = ActiveValue::Set(derive_verdict);
+                }
+                project_model.update(txn).await
+            })
+        })
+        .await?;
+    Ok(Json(project))
+}
+
+/// Deletes a project, fails with `409 Conflict` as long as it has reports or
+/// test runs.
+pub(crate) async fn delete_project(
+    Path(project_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<(), RgError> {
+    let project = find_by_id(&db, project_id).await?;
+    if project.find_related(Report).count(&db).await? > 0 {
+        return Err(eyre!("Project {} still has reports", project.name))
+            .with_status_code(StatusCode::CONFLICT);
+    }
+    if TestRun::find()
+        .filter(test_run::Column::ProjectId.eq(project.id))
+        .count(&db)
+        .await?
+        > 0
+    {
+        return Err(eyre!("Project {} still has test runs", project.name))
+            .with_status_code(StatusCode::CONFLICT);
+    }
+    project.delete(&db).await?;
+    Ok(())
+}
+
+pub(crate) async fn get_reports_for_project(
+    Path(project_id): Path<i32>,
+    Query(mut filter): Query<ReportFilter>,
+    RawQuery(query): RawQuery,
+    State(db): State<DatabaseConnection>,
+) -> Result<([(&'static str, String); 1], Json<Vec<report::Model>>), RgError> {
+    filter.properties = filter::property_filters(query.as_deref());
+    let project = find_by_id(&db, project_id).await?;
+    let select = filter
+        .select()
+        .filter(report::Column::ProjectId.eq(project.id));
+    let total_count = select.clone().count(&db).await?;
+    let reports = filter.paginate(select).all(&db).await?;
+    let reports = crate::report_metadata::load(&db, reports)
+        .await?
+        .into_iter()
+        .map(report::Model::with_original_offset)
+        .collect();
+    Ok((
+        [(filter::TOTAL_COUNT_HEADER, total_count.to_string())],
+        Json(reports),
+    ))
+}
+
+/// Whether the verdict of the report follows its test steps.
+pub(crate) async fn derives_verdict<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+) -> Result<bool, DbErr> {
+    if let Some(derive_verdict) = report.derive_verdict {
+        return Ok(derive_verdict);
+    }
+    Ok(report
+        .find_related(Project)
+        .one(db)
+        .await?
+        .is_some_and(|project| project.derive_verdict))
+}
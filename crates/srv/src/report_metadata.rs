@@ -0,0 +1,150 @@
+//! Properties and tags of reports, which are kept in their own tables.
+
+use entities::{prelude::*, *};
+
+use crate::error::{ErrorDetails, RgError, WithStatusCode};
+use axum::http::StatusCode;
+use eyre::eyre;
+use sea_orm::{
+    ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Fills in the properties and tags of the reports.
+pub(crate) async fn load<C: ConnectionTrait>(
+    db: &C,
+    mut reports: Vec<report::Model>,
+) -> Result<Vec<report::Model>, DbErr> {
+    let ids: Vec<_> = reports.iter().map(|report| report.id).collect();
+    let mut properties: HashMap<i32, BTreeMap<String, String>> = HashMap::new();
+    for property in ReportProperty::find()
+        .filter(report_property::Column::ReportId.is_in(ids.clone()))
+        .all(db)
+        .await?
+    {
+        properties
+            .entry(property.report_id)
+            .or_default()
+            .insert(property.key, property.value);
+    }
+    let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+    for tag in ReportTag::find()
+        .filter(report_tag::Column::ReportId.is_in(ids))
+        .order_by_asc(report_tag::Column::Tag)
+        .all(db)
+        .await?
+    {
+        tags.entry(tag.report_id).or_default().push(tag.tag);
+    }
+    for report in &mut reports {
+        report.properties = properties.remove(&report.id).unwrap_or_default();
+        report.tags = tags.remove(&report.id).unwrap_or_default();
+    }
+    Ok(reports)
+}
+
+pub(crate) async fn load_one<C: ConnectionTrait>(
+    db: &C,
+    report: report::Model,
+) -> Result<report::Model, DbErr> {
+    Ok(load(db, vec![report])
+        .await?
+        .pop()
+        .expect("one report in, one report out"))
+}
+
+/// Rejects empty property keys and tags, which could not be filtered for.
+pub(crate) fn validate<'a>(
+    keys: impl IntoIterator<Item = &'a String>,
+    tags: &[String],
+) -> Result<(), RgError> {
+    let invalid = |field: &str| {
+        RgError::from(eyre!("Property keys and tags must not be empty!"))
+            .with_status_code(StatusCode::BAD_REQUEST)
+            .with_details(ErrorDetails {
+                field: Some(field.to_string()),
+            })
+    };
+    if keys.into_iter().any(|key| key.trim().is_empty()) {
+        return Err(invalid("properties"));
+    }
+    if tags.iter().any(|tag| tag.trim().is_empty()) {
+        return Err(invalid("tags"));
+    }
+    Ok(())
+}
+
+/// Sets or, for `None` values, removes the given properties of a report.
+///
+/// With `replace` all other properties of the report are removed as well.
+pub(crate) async fn store_properties<C: ConnectionTrait>(
+    db: &C,
+    report_id: i32,
+    properties: BTreeMap<String, Option<String>>,
+    replace: bool,
+) -> Result<(), DbErr> {
+    let properties: BTreeMap<_, _> = properties
+        .into_iter()
+        .map(|(key, value)| (key.trim().to_string(), value))
+        .collect();
+    let mut delete =
+        ReportProperty::delete_many().filter(report_property::Column::ReportId.eq(report_id));
+    if !replace {
+        delete = delete.filter(report_property::Column::Key.is_in(properties.keys().cloned()));
+    }
+    delete.exec(db).await?;
+
+    let models: Vec<_> = properties
+        .into_iter()
+        .filter_map(|(key, value)| {
+            Some(report_property::ActiveModel {
+                report_id: ActiveValue::Set(report_id),
+                key: ActiveValue::Set(key),
+                value: ActiveValue::Set(value?),
+                ..Default::default()
+            })
+        })
+        .collect();
+    if !models.is_empty() {
+        ReportProperty::insert_many(models).exec(db).await?;
+    }
+    Ok(())
+}
+
+/// Replaces all tags of a report, duplicates are stored only once.
+pub(crate) async fn store_tags<C: ConnectionTrait>(
+    db: &C,
+    report_id: i32,
+    tags: &[String],
+) -> Result<(), DbErr> {
+    ReportTag::delete_many()
+        .filter(report_tag::Column::ReportId.eq(report_id))
+        .exec(db)
+        .await?;
+    let mut tags: Vec<_> = tags.iter().map(|tag| tag.trim().to_string()).collect();
+    tags.sort();
+    tags.dedup();
+    if !tags.is_empty() {
+        ReportTag::insert_many(tags.into_iter().map(|tag| report_tag::ActiveModel {
+            report_id: ActiveValue::Set(report_id),
+            tag: ActiveValue::Set(tag),
+            ..Default::default()
+        }))
+        .exec(db)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Removes all properties and tags of a report before it is deleted.
+pub(crate) async fn delete<C: ConnectionTrait>(db: &C, report_id: i32) -> Result<(), DbErr> {
+    ReportProperty::delete_many()
+        .filter(report_property::Column::ReportId.eq(report_id))
+        .exec(db)
+        .await?;
+    ReportTag::delete_many()
+        .filter(report_tag::Column::ReportId.eq(report_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,245 @@
+//! Endpoints for test runs, which group the reports of one execution of a
+//! test campaign.
+
+use entities::{prelude::*, *};
+
+use crate::config::Features;
+use crate::error::{ErrorDetails, RgError, WithStatusCode};
+use crate::extract::{self, Path, Query};
+use crate::filter::{self, ReportFilter, TestRunFilter};
+use axum::{
+    extract::{RawQuery, State},
+    http::StatusCode,
+    Json,
+};
+use eyre::eyre;
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, sea_query::Expr, ActiveModelTrait, ActiveValue, ColumnTrait,
+    ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter,
+    QuerySelect, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+async fn find_by_id<C: ConnectionTrait>(
+    db: &C,
+    test_run_id: i32,
+) -> Result<test_run::Model, RgError> {
+    TestRun::find_by_id(test_run_id)
+        .one(db)
+        .await?
+        .map(test_run::Model::with_original_offset)
+        .ok_or_else(|| eyre!("Could not find test run with id {test_run_id}!"))
+        .with_status_code(StatusCode::NOT_FOUND)
+}
+
+/// Ensures that a report can be assigned to the test run, which has to exist
+/// and belong to the same project as the report.
+pub(crate) async fn check_report_run<C: ConnectionTrait>(
+    db: &C,
+    test_run_id: i32,
+    project_id: i32,
+) -> Result<(), RgError> {
+    let run = TestRun::find_by_id(test_run_id).one(db).await?;
+    if run.map(|run| run.project_id) != Some(project_id) {
+        return Err(RgError::from(eyre!(
+            "Could not find test run with id {test_run_id} in the project of the report!"
+        ))
+        .with_status_code(StatusCode::BAD_REQUEST)
+        .with_details(ErrorDetails {
+            field: Some("test_run_id".to_string()),
+        }));
+    }
+    Ok(())
+}
+
+/// A test run together with the aggregated results of its reports
+#[derive(Serialize)]
+pub(crate) struct TestRunSummary {
+    #[serde(flatten)]
+    run: test_run::Model,
+    report_count: u64,
+    /// Number of reports per verdict
+    verdicts: BTreeMap<Verdict, u64>,
+    /// Number of successful reports
+    passed: u64,
+    /// Number of reports that failed or ran into an error
+    failed: u64,
+    /// Worst verdict of all reports, `None` if the run has no reports
+    verdict: Option<Verdict>,
+}
+
+async fn summarize<C: ConnectionTrait>(
+    db: &C,
+    run: test_run::Model,
+) -> Result<TestRunSummary, DbErr> {
+    let counts: Vec<(Verdict, i64)> = Report::find()
+        .select_only()
+        .column(report::Column::Verdict)
+        .column_as(report::Column::Id.count(), "count")
+        .filter(report::Column::TestRunId.eq(run.id))
+        .group_by(report::Column::Verdict)
+        .into_tuple()
+        .all(db)
+        .await?;
+    let verdicts: BTreeMap<_, _> = counts
+        .into_iter()
+        .map(|(verdict, count)| (verdict, count as u64))
+        .collect();
+    let count = |wanted: &[Verdict]| {
+        wanted
+            .iter()
+            .filter_map(|verdict| verdicts.get(verdict))
+            .sum()
+    };
+    Ok(TestRunSummary {
+        run,
+        report_count: verdicts.values().sum(),
+        passed: count(&[Verdict::Success]),
+        failed: count(&Verdict::FAILURES),
+        verdict: verdicts.keys().max().copied(),
+        verdicts,
+    })
+}
+
+#[derive(Deserialize)]
+pub(crate) struct NewTestRun {
+    project: String,
+    name: String,
+    start: DateTimeWithTimeZone,
+    #[serde(default)]
+    end: Option<DateTimeWithTimeZone>,
+    #[serde(default)]
+    trigger: Option<String>,
+}
+
+pub(crate) async fn add_test_run(
+    State(db): State<DatabaseConnection>,
+    State(features): State<Features>,
+    run: String,
+) -> Result<String, RgError> {
+    let run: NewTestRun = extract::json(&run)?;
+    let project = crate::projects::find_by_name(&db, features, &run.project).await?;
+
+    let run_model = test_run::ActiveModel {
+        project_id: ActiveValue::Set(project.id),
+        name: ActiveValue::Set(run.name),
+        start: ActiveValue::Set(date::to_utc(run.start)),
+        start_offset: ActiveValue::Set(date::offset_seconds(&run.start)),
+        end: ActiveValue::Set(run.end.map(date::to_utc)),
+        end_offset: ActiveValue::Set(run.end.as_ref().map(date::offset_seconds)),
+        trigger: ActiveValue::Set(run.trigger),
+        ..Default::default()
+    };
+    let res = TestRun::insert(run_model).exec(&db).await?;
+    Ok(res.last_insert_id.to_string())
+}
+
+pub(crate) async fn get_test_runs(
+    Query(filter): Query<TestRunFilter>,
+    State(db): State<DatabaseConnection>,
+) -> Result<([(&'static str, String); 1], Json<Vec<TestRunSummary>>), RgError> {
+    let select = filter.select();
+    let total_count = select.clone().count(&db).await?;
+    let runs = filter.paginate(select).all(&db).await?;
+    let mut summaries = Vec::with_capacity(runs.len());
+    for run in runs {
+        summaries.push(summarize(&db, run.with_original_offset()).await?);
+    }
+    Ok((
+        [(filter::TOTAL_COUNT_HEADER, total_count.to_string())],
+        Json(summaries),
+    ))
+}
+
+pub(crate) async fn get_single_test_run(
+    Path(test_run_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<TestRunSummary>, RgError> {
+    let run = find_by_id(&db, test_run_id).await?;
+    Ok(Json(summarize(&db, run).await?))
+}
+
+/// Changes to a test run, fields that are `None` are left untouched and
+/// nullable fields set to `null` are cleared
+#[derive(Deserialize)]
+pub(crate) struct TestRunPatch {
+    name: Option<String>,
+    start: Option<DateTimeWithTimeZone>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    end: Option<Option<DateTimeWithTimeZone>>,
+    #[serde(default, deserialize_with = "extract::nullable")]
+    trigger: Option<Option<String>>,
+}
+
+/// Updates the given fields of a test run, e.g. its `end` once it finished.
+pub(crate) async fn update_test_run(
+    Path(test_run_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+    patch: String,
+) -> Result<Json<TestRunSummary>, RgError> {
+    let patch: TestRunPatch = extract::json(&patch)?;
+    let run = find_by_id(&db, test_run_id).await?;
+
+    let mut run_model: test_run::ActiveModel = run.into();
+    if let Some(name) = patch.name {
+        run_model.name = ActiveValue::Set(name);
+    }
+    if let Some(start) = patch.start {
+        run_model.start = ActiveValue::Set(date::to_utc(start));
+        run_model.start_offset = ActiveValue::Set(date::offset_seconds(&start));
+    }
+    if let Some(end) = patch.end {
+        run_model.end = ActiveValue::Set(end.map(date::to_utc));
+        run_model.end_offset = ActiveValue::Set(end.as_ref().map(date::offset_seconds));
+    }
+    if let Some(trigger) = patch.trigger {
+        run_model.trigger = ActiveValue::Set(trigger);
+    }
+    let run = run_model.update(&db).await?.with_original_offset();
+    Ok(Json(summarize(&db, run).await?))
+}
+
+/// Deletes a test run, its reports are kept but no longer belong to a run.
+pub(crate) async fn delete_test_run(
+    Path(test_run_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<(), RgError> {
+    let run = find_by_id(&db, test_run_id).await?;
+    db.transaction(|txn| {
+        Box::pin(async move {
+            Report::update_many()
+                .col_expr(report::Column::TestRunId, Expr::value(Option::<i32>::None))
+                .filter(report::Column::TestRunId.eq(run.id))
+                .exec(txn)
+                .await?;
+            crate::baselines::unset(txn, project::Column::BaselineTestRunId, run.id).await?;
+            TestRun::delete_by_id(run.id).exec(txn).await?;
+            Ok::<_, DbErr>(())
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn get_reports_for_test_run(
+    Path(test_run_id): Path<i32>,
+    Query(mut filter): Query<ReportFilter>,
+    RawQuery(query): RawQuery,
+    State(db): State<DatabaseConnection>,
+) -> Result<([(&'static str, String); 1], Json<Vec<report::Model>>), RgError> {
+    filter.properties = filter::property_filters(query.as_deref());
+    let run = find_by_id(&db, test_run_id).await?;
+    let select = filter.select().filter(report::Column::TestRunId.eq(run.id));
+    let total_count = select.clone().count(&db).await?;
+    let reports = filter.paginate(select).all(&db).await?;
+    let reports = crate::report_metadata::load(&db, reports)
+        .await?
+        .into_iter()
+        .map(report::Model::with_original_offset)
+        .collect();
+    Ok((
+        [(filter::TOTAL_COUNT_HEADER, total_count.to_string())],
+        Json(reports),
+    ))
+}
@@ -0,0 +1,146 @@
+//! Triage of failures, i.e. classifying why a report or test step failed and
+//! linking it to the ticket that tracks it.
+
+use entities::{prelude::*, *};
+
+use crate::error::{ErrorDetails, RgError, WithStatusCode};
+use crate::extract::{self, Path, Query};
+use crate::filter::{self, TriageFilter};
+use axum::{extract::State, http::StatusCode, Json};
+use eyre::eyre;
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+};
+use serde::Deserialize;
+
+async fn find_by_id<C: ConnectionTrait>(db: &C, triage_id: i32) -> Result<triage::Model, RgError> {
+    Triage::find_by_id(triage_id)
+        .one(db)
+        .await?
+        .map(triage::Model::with_original_offset)
+        .ok_or_else(|| eyre!("Could not find triage with id {triage_id}!"))
+        .with_status_code(StatusCode::NOT_FOUND)
+}
+
+#[derive(Deserialize)]
+pub(crate) struct NewTriage {
+    /// Step of the report the entry is about, the whole report if missing
+    #[serde(default)]
+    test_step_id: Option<i32>,
+    status: TriageStatus,
+    category: TriageCategory,
+    #[serde(default)]
+    ticket: Option<String>,
+    #[serde(default)]
+    comment: Option<String>,
+    author: String,
+    /// Defaults to the time of the request
+    #[serde(default)]
+    date: Option<DateTimeWithTimeZone>,
+}
+
+/// Adds a triage entry to a report or one of its test steps, earlier entries
+/// are kept as history.
+pub(crate) async fn add_triage(
+    Path(report_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+    triage: String,
+) -> Result<String, RgError> {
+    let triage: NewTriage = extract::json(&triage)?;
+    if triage.author.trim().is_empty() {
+        return Err(
+            RgError::from(eyre!("The author of a triage must not be empty"))
+                .with_status_code(StatusCode::BAD_REQUEST)
+                .with_details(ErrorDetails {
+                    field: Some("author".to_string()),
+                }),
+        );
+    }
+    let report = crate::find_report(&db, report_id).await?;
+    if let Some(test_step_id) = triage.test_step_id {
+        let step = TestStep::find_by_id(test_step_id).one(&db).await?;
+        if step.map(|step| step.report_id) != Some(report.id) {
+            return Err(RgError::from(eyre!(
+                "Could not find test step with id {test_step_id} in report {}!",
+                report.id
+            ))
+            .with_status_code(StatusCode::BAD_REQUEST)
+            .with_details(ErrorDetails {
+                field: Some("test_step_id".to_string()),
+            }));
+        }
+    }
+
+    let date = triage.date.unwrap_or_else(|| chrono::Utc::now().into());
+    let triage_model = triage::ActiveModel {
+        report_id: ActiveValue::Set(report.id),
+        test_step_id: ActiveValue::Set(triage.test_step_id),
+        status: ActiveValue::Set(triage.status),
+        category: ActiveValue::Set(triage.category),
+        ticket: ActiveValue::Set(triage.ticket),
+        comment: ActiveValue::Set(triage.comment),
+        author: ActiveValue::Set(triage.author),
+        date: ActiveValue::Set(date::to_utc(date)),
+        date_offset: ActiveValue::Set(date::offset_seconds(&date)),
+        ..Default::default()
+    };
+    let res = Triage::insert(triage_model).exec(&db).await?;
+    Ok(res.last_insert_id.to_string())
+}
+
+/// Returns the triage entries of a report and its test steps in
+/// chronological order.
+pub(crate) async fn get_triage_for_report(
+    Path(report_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<Vec<triage::Model>>, RgError> {
+    let report = crate::find_report(&db, report_id).await?;
+    let entries = Triage::find()
+        .filter(triage::Column::ReportId.eq(report.id))
+        .order_by_asc(triage::Column::Date)
+        .order_by_asc(triage::Column::Id)
+        .all(&db)
+        .await?;
+    Ok(Json(
+        entries
+            .into_iter()
+            .map(triage::Model::with_original_offset)
+            .collect(),
+    ))
+}
+
+pub(crate) async fn get_triage(
+    Query(filter): Query<TriageFilter>,
+    State(db): State<DatabaseConnection>,
+) -> Result<([(&'static str, String); 1], Json<Vec<triage::Model>>), RgError> {
+    let select = filter.select();
+    let total_count = select.clone().count(&db).await?;
+    let entries = filter.paginate(select).all(&db).await?;
+    Ok((
+        [(filter::TOTAL_COUNT_HEADER, total_count.to_string())],
+        Json(
+            entries
+                .into_iter()
+                .map(triage::Model::with_original_offset)
+                .collect(),
+        ),
+    ))
+}
+
+pub(crate) async fn get_single_triage(
+    Path(triage_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<Json<triage::Model>, RgError> {
+    Ok(Json(find_by_id(&db, triage_id).await?))
+}
+
+/// Deletes a triage entry, e.g. one that was added by mistake.
+pub(crate) async fn delete_triage(
+    Path(triage_id): Path<i32>,
+    State(db): State<DatabaseConnection>,
+) -> Result<(), RgError> {
+    let triage = find_by_id(&db, triage_id).await?;
+    Triage::delete_by_id(triage.id).exec(&db).await?;
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::io::Read;
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use eyre::eyre;
+
+/// Upload test results produced by other tools to result guide
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Result guide host name
+    #[arg(long)]
+    host: Option<String>,
+    /// Result guide host port
+    #[arg(short, long)]
+    port: Option<u16>,
+    /// Project the reports belong to
+    #[arg(long)]
+    project: String,
+    /// Format of the test results
+    #[arg(short, long, value_enum)]
+    format: Format,
+    /// Report name for formats that do not contain one
+    #[arg(short, long)]
+    name: Option<String>,
+    /// File with the test results, read from stdin if omitted
+    file: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// JUnit XML
+    Junit,
+    /// ASAM ATX, e.g. exported by ecu.test
+    Atx,
+    /// JSON output of `cargo test -- -Z unstable-options --format json`
+    Libtest,
+}
+
+impl Format {
+    fn endpoint(self) -> &'static str {
+        match self {
+            Format::Junit => "junit",
+            Format::Atx => "atx",
+            Format::Libtest => "libtest",
+        }
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let cli = Cli::parse();
+
+    let document = match &cli.file {
+        Some(file) => std::fs::read_to_string(file)?,
+        None => {
+            let mut document = String::new();
+            std::io::stdin().read_to_string(&mut document)?;
+            document
+        }
+    };
+
+    let host_name = cli.host.unwrap_or("localhost".to_string());
+    let port = cli.port.unwrap_or(3000);
+    let command_url = format!("http://{host_name}:{port}/import/{}", cli.format.endpoint());
+
+    let mut query = vec![("project", cli.project)];
+    query.extend(cli.name.map(|name| ("name", name)));
+
+    let response = reqwest::blocking::Client::new()
+        .post(command_url)
+        .query(&query)
+        .body(document)
+        .send()?;
+    let status = response.status();
+    let body = response.text()?;
+    if !status.is_success() {
+        let message = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|error| Some(error.get("message")?.as_str()?.to_string()))
+            .unwrap_or(body);
+        return Err(eyre!("Upload failed with {status}: {message}"));
+    }
+
+    let report_ids: Vec<i32> = serde_json::from_str(&body)?;
+    for report_id in report_ids {
+        println!("{report_id}");
+    }
+    Ok(())
+}
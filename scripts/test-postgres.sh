@@ -0,0 +1,37 @@
+#!/bin/sh
+# Runs the test suite against a throwaway PostgreSQL server.
+#
+# Needs `initdb`, `pg_ctl` and `postgres` in the PATH. PostgreSQL refuses to
+# run as root, so the server is started as $PG_USER (default `postgres`) in
+# that case. Arguments are passed to `cargo test`.
+set -eu
+
+PG_PORT=${PG_PORT:-54329}
+PG_DIR=$(mktemp -d)
+
+as_pg_user() {
+    if [ "$(id -u)" = 0 ]; then
+        runuser -u "${PG_USER:-postgres}" -- "$@"
+    else
+        "$@"
+    fi
+}
+
+cleanup() {
+    as_pg_user pg_ctl -D "$PG_DIR/data" -m immediate stop >/dev/null 2>&1 || true
+    rm -rf "$PG_DIR"
+}
+trap cleanup EXIT INT TERM
+
+if [ "$(id -u)" = 0 ]; then
+    chown "${PG_USER:-postgres}" "$PG_DIR"
+fi
+# Started from $PG_DIR, the working directory may not be accessible by $PG_USER
+(
+    cd "$PG_DIR"
+    as_pg_user initdb -D "$PG_DIR/data" -U postgres --auth=trust >/dev/null
+    as_pg_user pg_ctl -D "$PG_DIR/data" -l "$PG_DIR/postgres.log" -w \
+        -o "-p $PG_PORT -k $PG_DIR -c listen_addresses=127.0.0.1" start >/dev/null
+)
+
+RESULT_GUIDE_TEST_POSTGRES_URL="postgres://postgres@127.0.0.1:$PG_PORT" cargo test --workspace "$@"